@@ -0,0 +1,30 @@
+//! Writes every note carrying a tag (or a tag nested below it) to a directory
+//! as markdown, in the format `qnote import` reads back.
+//!
+//! ```sh
+//! cargo run --example export_tag -- notes.db work exported/
+//! ```
+
+use std::path::Path;
+
+use anyhow::{Context, Result};
+use qnote::{Database, NoteQuery, config::DatabaseConfig, note_to_markdown, utils::sanitize_filename};
+
+fn main() -> Result<()> {
+	let args: Vec<String> = std::env::args().skip(1).collect();
+	let [db_path, tag, dir] = args.as_slice() else {
+		anyhow::bail!("usage: export_tag <database> <tag> <directory>");
+	};
+	let db = Database::open(db_path, &DatabaseConfig::default())?;
+
+	let summaries = db.query_notes(&NoteQuery::new().tags_any([tag.clone()]).include_archived(true))?;
+	let ids: Vec<i64> = summaries.iter().map(|note| note.id).collect();
+
+	std::fs::create_dir_all(dir)?;
+	for note in db.get_notes(&ids)? {
+		let path = Path::new(dir).join(format!("{}.md", sanitize_filename(&note.title)));
+		std::fs::write(&path, note_to_markdown(&note)).with_context(|| format!("Could not write {}", path.display()))?;
+	}
+	println!("Exported {} note(s) tagged '{tag}' to {dir}", ids.len());
+	Ok(())
+}
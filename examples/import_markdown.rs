@@ -0,0 +1,34 @@
+//! Imports markdown files into a qnote database, skipping empty files. Files
+//! exported by qnote update the note they came from instead of duplicating it.
+//!
+//! ```sh
+//! cargo run --example import_markdown -- notes.db inbox/*.md
+//! ```
+
+use anyhow::{Context, Result};
+use qnote::{Database, config::DatabaseConfig, db::ImportOutcome, parse_markdown_file};
+
+fn main() -> Result<()> {
+	let mut args = std::env::args().skip(1);
+	let db_path = args.next().context("usage: import_markdown <database> <file.md>...")?;
+	let db = Database::new(&db_path, &DatabaseConfig::default())?;
+
+	// Parse everything first so a bad file imports nothing
+	let mut notes = Vec::new();
+	for path in args {
+		let text = std::fs::read_to_string(&path).with_context(|| format!("Could not read {path}"))?;
+		if let Some(parsed) = parse_markdown_file(&text) {
+			notes.push(parsed.into_note());
+		}
+	}
+
+	let outcomes = db.import_notes(&notes)?;
+	let count = |matches: fn(&ImportOutcome) -> bool| outcomes.iter().filter(|outcome| matches(outcome)).count();
+	println!(
+		"Imported {} note(s) into {db_path}, updated {}, unchanged {}",
+		count(|outcome| matches!(outcome, ImportOutcome::Created(_))),
+		count(|outcome| matches!(outcome, ImportOutcome::Updated(_))),
+		count(|outcome| matches!(outcome, ImportOutcome::Unchanged(_)))
+	);
+	Ok(())
+}
@@ -3,13 +3,19 @@
 //! Defines CLI argument structures and command enums.
 //! Command implementations are in the `commands` module.
 
-use clap::{Parser, Subcommand};
+use clap::{Args, Parser, Subcommand};
 
 /// Main CLI structure parsed by clap.
 #[derive(Parser)]
 #[command(name = "qnote")]
 #[command(about = "A quick note-taking app", long_about = None)]
 pub struct Cli {
+	/// Database file to use instead of the configured one
+	#[arg(long, global = true, value_name = "PATH")]
+	pub db:      Option<String>,
+	/// Named vault to use (see `qnote vault list`)
+	#[arg(long, global = true, conflicts_with = "db")]
+	pub vault:   Option<String>,
 	#[command(subcommand)]
 	pub command: Option<Commands>,
 }
@@ -23,27 +29,81 @@ pub enum SortBy {
 	Title,
 }
 
+/// Dates to give new notes instead of the current time.
+#[derive(Args)]
+pub struct DateArgs {
+	/// Creation date (YYYY-MM-DD or RFC 3339), e.g. for notes written up later
+	#[arg(long, value_name = "DATE")]
+	pub created: Option<String>,
+	/// Last update date (defaults to the creation date)
+	#[arg(long, value_name = "DATE")]
+	pub updated: Option<String>,
+}
+
+/// Filters, order and paging for the list command.
+#[derive(Args)]
+pub struct ListArgs {
+	/// Only notes with one of these tags (comma-separated)
+	#[arg(short, long)]
+	pub tag:           Option<String>,
+	/// Require every tag given with --tag instead of any one
+	#[arg(long, requires = "tag")]
+	pub all_tags:      bool,
+	/// Only notes matching a search query
+	#[arg(short, long, value_name = "QUERY")]
+	pub r#match:       Option<String>,
+	/// Only notes whose property KEY equals VALUE (repeatable)
+	#[arg(short, long = "where", value_name = "KEY=VALUE")]
+	pub r#where:       Vec<String>,
+	/// Only notes in this notebook or a notebook below it (e.g. Work/Project)
+	#[arg(long, value_name = "PATH")]
+	pub notebook:      Option<String>,
+	/// Only notes updated on or after this date (YYYY-MM-DD or RFC 3339)
+	#[arg(long, value_name = "DATE")]
+	pub since:         Option<String>,
+	/// Only notes updated on or before this date
+	#[arg(long, value_name = "DATE")]
+	pub until:         Option<String>,
+	/// Only notes created on or after this date
+	#[arg(long, value_name = "DATE")]
+	pub created_since: Option<String>,
+	/// Only notes created on or before this date
+	#[arg(long, value_name = "DATE")]
+	pub created_until: Option<String>,
+	#[arg(short, long)]
+	pub oneline:       bool,
+	#[arg(short, long, default_value = "updated")]
+	pub sort:          SortBy,
+	/// Reverse the sort order (oldest or Z→A first)
+	#[arg(short, long)]
+	pub reverse:       bool,
+	#[arg(short, long)]
+	pub limit:         Option<usize>,
+	/// Skip this many notes (for paging with --limit)
+	#[arg(long, default_value_t = 0)]
+	pub offset:        usize,
+	/// Include archived notes
+	#[arg(long)]
+	pub archived:      bool,
+}
+
 /// Available CLI commands.
 #[derive(Subcommand)]
 pub enum Commands {
 	/// Add a new note with title, content, and optional tags
 	Add {
-		title:   String,
-		content: String,
+		title:    String,
+		content:  String,
 		#[arg(short, long)]
-		tags:    Option<String>,
+		tags:     Option<String>,
+		/// Notebook to file the note in (e.g. Work/Project)
+		#[arg(long, value_name = "PATH")]
+		notebook: Option<String>,
+		#[command(flatten)]
+		dates:    DateArgs,
 	},
 	/// List all notes
-	List {
-		#[arg(short, long)]
-		tag:     Option<String>,
-		#[arg(short, long)]
-		oneline: bool,
-		#[arg(short, long, default_value = "updated")]
-		sort:    SortBy,
-		#[arg(short, long)]
-		limit:   Option<usize>,
-	},
+	List(ListArgs),
 	/// Show a specific note (by ID or title pattern)
 	Show { id_or_title: String },
 	/// Edit a note (by ID or title pattern)
@@ -56,26 +116,121 @@ pub enum Commands {
 		#[arg(short = 'g', long)]
 		tags:        Option<String>,
 	},
-	/// Delete a note (by ID or title pattern)
+	/// Move a note to the trash (by ID or title pattern)
 	Delete {
 		id_or_title: String,
 		#[arg(short, long)]
 		yes:         bool,
 	},
+	/// Pin a note to the top of every list
+	Pin { id_or_title: String },
+	/// Unpin a note
+	Unpin { id_or_title: String },
+	/// Archive a note (hidden from list and search unless --archived)
+	Archive { id_or_title: String },
+	/// Move a note out of the archive
+	Unarchive { id_or_title: String },
+	/// List the [[wikilinks]] written in a note
+	Links { id_or_title: String },
+	/// List notes that link to a note
+	Backlinks { id_or_title: String },
+	/// Attach a file to a note
+	Attach { id_or_title: String, file: String },
+	/// List a note's attachments
+	Attachments { id_or_title: String },
+	/// Remove an attachment
+	Detach { attachment_id: i64 },
+	/// Save an attachment to a file
+	Extract {
+		attachment_id: i64,
+		/// Output path (defaults to the attachment's filename)
+		#[arg(short, long)]
+		output:        Option<String>,
+	},
+	/// Show previous versions of a note
+	History { id_or_title: String },
+	/// Show changes between a revision and another revision or the current note
+	Diff { id_or_title: String, from: i64, to: Option<i64> },
+	/// Restore a note to a previous revision
+	Restore { id_or_title: String, revision: i64 },
 	/// Search notes by keyword
-	Search { query: String },
+	Search {
+		query:    String,
+		/// Only notes whose property KEY equals VALUE (repeatable)
+		#[arg(short, long = "where", value_name = "KEY=VALUE")]
+		r#where:  Vec<String>,
+		/// Only notes in this notebook or a notebook below it
+		#[arg(long, value_name = "PATH")]
+		notebook: Option<String>,
+		/// Include archived notes
+		#[arg(long)]
+		archived: bool,
+	},
 	/// Export a note to a markdown file
 	Export {
 		id_or_title: String,
-		#[arg(short, long)]
+		#[arg(short, long, conflicts_with = "dir")]
 		output:      Option<String>,
+		/// Export into a directory, together with the note's attachments
+		#[arg(short, long)]
+		dir:         Option<String>,
 	},
 	/// Import notes from markdown files
-	Import { files: Vec<String> },
+	Import {
+		files:  Vec<String>,
+		/// Import nothing if any file is missing or cannot be parsed
+		#[arg(long)]
+		atomic: bool,
+		/// Dates for the notes created by the import (updated notes are unaffected)
+		#[command(flatten)]
+		dates:  DateArgs,
+	},
 	/// List all tags with note counts
-	Tags,
+	Tags {
+		/// Show nested tags (`parent/child`) as a tree, counting each tag's
+		/// notes together with those of the tags below it
+		#[arg(long)]
+		tree: bool,
+	},
+	/// Edit tags across all notes
+	Tag {
+		#[command(subcommand)]
+		command: TagCommand,
+	},
+	/// Create, list and delete notebooks, and file notes in them
+	Notebook {
+		#[command(subcommand)]
+		command: NotebookCommand,
+	},
+	/// Set, remove or list note properties
+	Prop {
+		#[command(subcommand)]
+		command: PropCommand,
+	},
+	/// List `- [ ]` tasks across all notes
+	Tasks {
+		/// Only unchecked tasks
+		#[arg(long)]
+		open:     bool,
+		/// Only tasks in notes with one of these tags (comma-separated)
+		#[arg(short, long)]
+		tag:      Option<String>,
+		/// Include tasks in archived notes
+		#[arg(long)]
+		archived: bool,
+	},
+	/// Check or uncheck a task in its note
+	Task {
+		#[command(subcommand)]
+		command: TaskCommand,
+	},
 	/// Show statistics about notes
 	Stats,
+	/// Manage notes in the trash
+	Trash {
+		#[command(subcommand)]
+		command: TrashCommand,
+	},
 	/// Open TUI interface
 	Tui,
 	/// Generate a default configuration file
@@ -83,4 +238,181 @@ pub enum Commands {
 		#[arg(short, long)]
 		show: bool,
 	},
+	/// Database maintenance
+	Db {
+		#[command(subcommand)]
+		command: DbCommand,
+	},
+	/// Check the database for corruption and a stale search index
+	Doctor {
+		/// Rebuild the search index, repair what can be repaired, then vacuum and
+		/// optimize
+		#[arg(long)]
+		fix: bool,
+	},
+	/// Back up the database to a timestamped file
+	Backup {
+		/// Directory to write the backup to (default: backup.dir in the config)
+		#[arg(long)]
+		to: Option<String>,
+	},
+	/// Replace the database with a backup, after checking the backup
+	RestoreBackup {
+		/// Backup file to restore
+		file: String,
+		#[arg(short, long)]
+		yes:  bool,
+	},
+	/// Encrypt the vault with a passphrase (or change the passphrase)
+	Encrypt,
+	/// Store an encrypted vault in plain text again
+	Decrypt,
+	/// Manage named vaults (separate note databases)
+	Vault {
+		#[command(subcommand)]
+		command: VaultCommand,
+	},
+}
+
+/// Tag management subcommands.
+#[derive(Subcommand)]
+pub enum TagCommand {
+	/// Rename a tag on every note (merges if the new tag already exists)
+	Rename {
+		old:     String,
+		new:     String,
+		/// List the notes that would change without changing them
+		#[arg(long)]
+		dry_run: bool,
+	},
+	/// Merge several tags into one on every note
+	Merge {
+		#[arg(required = true)]
+		sources: Vec<String>,
+		/// Tag to merge into
+		#[arg(long)]
+		into:    String,
+		/// List the notes that would change without changing them
+		#[arg(long)]
+		dry_run: bool,
+	},
+	/// Remove a tag (and the tags nested below it) from every note
+	Delete {
+		tag:     String,
+		#[arg(short, long)]
+		yes:     bool,
+		/// List the notes that would change without changing them
+		#[arg(long)]
+		dry_run: bool,
+	},
+	/// Add a tag to several notes at once
+	Add {
+		tag:     String,
+		/// Notes to tag (comma-separated IDs or title patterns)
+		#[arg(long, value_delimiter = ',', required = true)]
+		note:    Vec<String>,
+		/// List the notes that would change without changing them
+		#[arg(long)]
+		dry_run: bool,
+	},
+	/// Remove a tag from several notes at once
+	Remove {
+		tag:     String,
+		/// Notes to untag (comma-separated IDs or title patterns)
+		#[arg(long, value_delimiter = ',', required = true)]
+		note:    Vec<String>,
+		/// List the notes that would change without changing them
+		#[arg(long)]
+		dry_run: bool,
+	},
+}
+
+/// Notebook subcommands. Notebooks are given by path, e.g. `Work/Project`.
+#[derive(Subcommand)]
+pub enum NotebookCommand {
+	/// Create a notebook, along with any missing notebooks above it
+	Create { path: String },
+	/// Show the notebook tree with note counts
+	List,
+	/// File notes in a notebook, or take them out of any notebook if none is
+	/// given
+	Move {
+		notebook: Option<String>,
+		/// Notes to move (comma-separated IDs or title patterns)
+		#[arg(long, value_delimiter = ',', required = true)]
+		note:     Vec<String>,
+	},
+	/// Delete a notebook and the notebooks below it (their notes are kept)
+	Delete {
+		path: String,
+		#[arg(short, long)]
+		yes:  bool,
+	},
+}
+
+/// Note property subcommands.
+#[derive(Subcommand)]
+pub enum PropCommand {
+	/// Set a property on a note (true/false, numbers and YYYY-MM-DD dates are
+	/// typed; quote a value to keep it as text)
+	Set { id_or_title: String, key: String, value: String },
+	/// Remove a property from a note
+	Unset { id_or_title: String, key: String },
+	/// List a note's properties, or every property name in use
+	List { id_or_title: Option<String> },
+}
+
+/// Task subcommands. Tasks are given as NOTE:LINE, as printed by `qnote tasks`.
+#[derive(Subcommand)]
+pub enum TaskCommand {
+	/// Check a task
+	Done {
+		#[arg(value_name = "NOTE:LINE")]
+		task: String,
+	},
+	/// Uncheck a task
+	Reopen {
+		#[arg(value_name = "NOTE:LINE")]
+		task: String,
+	},
+}
+
+/// Trash subcommands.
+#[derive(Subcommand)]
+pub enum TrashCommand {
+	/// List notes in the trash
+	List,
+	/// Move a note out of the trash
+	Restore { id: i64 },
+	/// Permanently delete every note in the trash
+	Empty {
+		#[arg(short, long)]
+		yes: bool,
+	},
+}
+
+/// Database maintenance subcommands.
+#[derive(Subcommand)]
+pub enum DbCommand {
+	/// Apply pending schema migrations
+	Migrate {
+		/// List pending migrations without applying them
+		#[arg(long)]
+		dry_run: bool,
+	},
+}
+
+/// Vault subcommands.
+#[derive(Subcommand)]
+pub enum VaultCommand {
+	/// List configured vaults
+	List,
+	/// Add a vault to the config and create its database
+	Create {
+		name: String,
+		/// Database file (defaults to <name>.db in the qnote data directory)
+		path: Option<String>,
+	},
+	/// Open a vault by default ("default" goes back to the standard database)
+	Switch { name: String },
 }
@@ -0,0 +1,63 @@
+use std::{fs, path::Path};
+
+use anyhow::{Context, Result};
+use qnote::{db::Database, utils::{format_date_full, format_size}};
+
+use super::interaction::resolve_note;
+
+/// Handles the attach command - stores a file with a note
+pub fn handle_attach(db: &Database, id_or_title: &str, file: &str) -> Result<()> {
+	let id = resolve_note(db, id_or_title)?;
+	let path = Path::new(file);
+	let filename =
+		path.file_name().and_then(|name| name.to_str()).ok_or_else(|| anyhow::anyhow!("Not a file: {file}"))?;
+	let data = fs::read(path).with_context(|| format!("Could not read {file}"))?;
+
+	let attachment_id = db.add_attachment(id, filename, &data)?;
+	println!("Attached {filename} ({}) to note {id} as attachment {attachment_id}.", format_size(data.len() as u64));
+	Ok(())
+}
+
+/// Handles the attachments command - lists the files attached to a note
+pub fn handle_attachments(db: &Database, id_or_title: &str) -> Result<()> {
+	let id = resolve_note(db, id_or_title)?;
+	let attachments = db.list_attachments(id)?;
+
+	if attachments.is_empty() {
+		println!("Note {id} has no attachments.");
+	} else {
+		println!("Attachments of note {id}:");
+		for attachment in attachments {
+			println!(
+				"  {:>4}  {}  {:>9}  {}  {}  sha256:{}",
+				attachment.id,
+				attachment.filename,
+				format_size(attachment.size),
+				attachment.mime_type,
+				format_date_full(&attachment.created_at),
+				&attachment.sha256[..12]
+			);
+		}
+	}
+	Ok(())
+}
+
+/// Handles the detach command - deletes an attachment
+pub fn handle_detach(db: &Database, attachment_id: i64) -> Result<()> {
+	let attachment =
+		db.get_attachment(attachment_id)?.ok_or_else(|| anyhow::anyhow!("Attachment {attachment_id} not found"))?;
+	db.remove_attachment(attachment_id)?;
+	println!("Removed {} from note {}.", attachment.filename, attachment.note_id);
+	Ok(())
+}
+
+/// Handles the extract command - writes an attachment back to disk
+pub fn handle_extract(db: &Database, attachment_id: i64, output: Option<String>) -> Result<()> {
+	let attachment =
+		db.get_attachment(attachment_id)?.ok_or_else(|| anyhow::anyhow!("Attachment {attachment_id} not found"))?;
+	let output = output.unwrap_or(attachment.filename);
+
+	fs::write(&output, db.attachment_data(attachment_id)?)?;
+	println!("Extracted to: {output}");
+	Ok(())
+}
@@ -0,0 +1,148 @@
+use std::{fs, path::{Path, PathBuf}};
+
+use anyhow::{Context, Result};
+use chrono::{Local, NaiveDateTime, TimeDelta};
+use qnote::{config::{data_dir, expand_home}, db::Database};
+
+use super::{interaction::confirm, open_database};
+use crate::settings::Config;
+
+/// Format of the timestamp in backup file names, e.g.
+/// `notes-20250918-143000.db`.
+const TIMESTAMP_FORMAT: &str = "%Y%m%d-%H%M%S";
+
+/// Handles the backup command - writes a timestamped copy of the database and
+/// deletes the oldest backups beyond `backup.keep`
+pub fn handle_backup(db: &Database, config: &Config, db_path: &Path, to: Option<String>) -> Result<()> {
+	let dir = match to {
+		Some(dir) => expand_home(&dir),
+		None => backup_dir(config)?,
+	};
+	let (path, removed) = create_backup(db, config, db_path, &dir)?;
+
+	println!("Backed up to {}", path.display());
+	if removed > 0 {
+		println!("Removed {removed} old backup(s).");
+	}
+	Ok(())
+}
+
+/// Runs the automatic backup set with `backup.auto` if one is due. `tui_closed`
+/// tells whether the TUI was just closed. Failures are reported without failing
+/// the command.
+pub fn auto_backup(db: &Database, config: &Config, db_path: &Path, tui_closed: bool) {
+	let due = match config.backup.auto.as_str() {
+		"exit" => tui_closed,
+		"daily" => match backup_dir(config).and_then(|dir| list_backups(&dir, &db_stem(db_path))) {
+			Ok(backups) => backups.first().is_none_or(|(taken, _)| Local::now().naive_local() - *taken >= TimeDelta::days(1)),
+			Err(_) => true,
+		},
+		_ => false,
+	};
+	if due && let Err(e) = backup_dir(config).and_then(|dir| create_backup(db, config, db_path, &dir)) {
+		eprintln!("Warning: automatic backup failed: {e:#}");
+	}
+}
+
+/// Handles the restore-backup command - checks a backup and swaps it in for the
+/// database, keeping the current file next to it
+pub fn handle_restore_backup(config: &Config, db_path: &Path, file: &str, yes: bool) -> Result<()> {
+	let backup = expand_home(file);
+	if !backup.is_file() {
+		anyhow::bail!("No such backup: {}", backup.display());
+	}
+
+	// Check a copy, so that opening it cannot change the backup itself
+	let staged = db_path.with_extension("restore");
+	let staged_str = staged.to_str().ok_or_else(|| anyhow::anyhow!("Invalid database path"))?;
+	fs::copy(&backup, &staged).context("Failed to copy backup")?;
+	if let Err(e) = open_database(staged_str, config, false).and_then(|db| db.verify()) {
+		remove_database_files(&staged);
+		return Err(e.context(format!("{} is not a usable backup", backup.display())));
+	}
+
+	println!("Backup {} is intact.", backup.display());
+	if !yes && !confirm(&format!("Replace {} with this backup?", db_path.display())) {
+		remove_database_files(&staged);
+		println!("Cancelled.");
+		return Ok(());
+	}
+
+	if db_path.exists() {
+		let previous = PathBuf::from(format!("{}.before-restore", db_path.display()));
+		remove_database_files(&previous);
+		for suffix in ["", "-wal", "-shm"] {
+			let file = PathBuf::from(format!("{}{suffix}", db_path.display()));
+			if file.exists() {
+				fs::rename(&file, format!("{}{suffix}", previous.display()))
+					.context("Failed to move the current database aside")?;
+			}
+		}
+		println!("The previous database was kept as {}", previous.display());
+	}
+	fs::rename(&staged, db_path).context("Failed to restore backup")?;
+	println!("Restored {}", db_path.display());
+	Ok(())
+}
+
+/// Returns the configured backup directory.
+fn backup_dir(config: &Config) -> Result<PathBuf> {
+	match &config.backup.dir {
+		Some(dir) => Ok(expand_home(dir)),
+		None => Ok(data_dir()?.join("backups")),
+	}
+}
+
+/// Backups are named after the database file, so vaults can share a directory.
+fn db_stem(db_path: &Path) -> String {
+	db_path.file_stem().map_or_else(|| "notes".to_string(), |stem| stem.to_string_lossy().into_owned())
+}
+
+/// Backs up the database into `dir` and prunes old backups. Returns the new
+/// backup and the number of backups removed.
+fn create_backup(db: &Database, config: &Config, db_path: &Path, dir: &Path) -> Result<(PathBuf, usize)> {
+	fs::create_dir_all(dir).context("Failed to create backup directory")?;
+	let stem = db_stem(db_path);
+	let path = dir.join(format!("{stem}-{}.db", Local::now().format(TIMESTAMP_FORMAT)));
+	db.backup_to(&path)?;
+
+	let mut removed = 0;
+	if config.backup.keep > 0 {
+		for (_, old) in list_backups(dir, &stem)?.into_iter().skip(config.backup.keep) {
+			fs::remove_file(&old).with_context(|| format!("Failed to remove old backup {}", old.display()))?;
+			removed += 1;
+		}
+	}
+	Ok((path, removed))
+}
+
+/// Lists the backups of a database in `dir`, newest first.
+fn list_backups(dir: &Path, stem: &str) -> Result<Vec<(NaiveDateTime, PathBuf)>> {
+	let entries = match fs::read_dir(dir) {
+		Ok(entries) => entries,
+		Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+		Err(e) => return Err(e).context("Failed to read backup directory"),
+	};
+
+	let mut backups = Vec::new();
+	for entry in entries {
+		let path = entry?.path();
+		let taken = path
+			.file_name()
+			.and_then(|name| name.to_str())
+			.and_then(|name| name.strip_prefix(stem)?.strip_prefix('-')?.strip_suffix(".db"))
+			.and_then(|timestamp| NaiveDateTime::parse_from_str(timestamp, TIMESTAMP_FORMAT).ok());
+		if let Some(taken) = taken {
+			backups.push((taken, path));
+		}
+	}
+	backups.sort_by_key(|(taken, _)| std::cmp::Reverse(*taken));
+	Ok(backups)
+}
+
+/// Removes a database file with its write-ahead log, ignoring missing files.
+fn remove_database_files(path: &Path) {
+	for suffix in ["", "-wal", "-shm"] {
+		let _ = fs::remove_file(format!("{}{suffix}", path.display()));
+	}
+}
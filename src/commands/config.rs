@@ -1,6 +1,7 @@
 use anyhow::Result;
 
-use crate::{config::Config, utils::confirm};
+use super::interaction::confirm;
+use crate::settings::Config;
 
 /// Handles the config command - generates or shows configuration
 pub fn handle_config(show: bool) -> Result<()> {
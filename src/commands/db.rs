@@ -0,0 +1,110 @@
+use anyhow::Result;
+use qnote::db::{Database, HealthReport, LATEST_VERSION};
+
+use crate::cli::DbCommand;
+
+/// Handles the db command - database maintenance tasks
+pub fn handle_db(db: &Database, command: DbCommand) -> Result<()> {
+	match command {
+		DbCommand::Migrate { dry_run } => handle_migrate(db, dry_run),
+	}
+}
+
+/// Applies pending schema migrations, or lists them with `--dry-run`
+fn handle_migrate(db: &Database, dry_run: bool) -> Result<()> {
+	let version = db.schema_version()?;
+	println!("Schema version: {version} (latest: {LATEST_VERSION})");
+
+	if dry_run {
+		let pending = db.pending_migrations()?;
+		if pending.is_empty() {
+			println!("Database is up to date.");
+		} else {
+			println!("Pending migrations:");
+			for (version, description) in pending {
+				println!("  {version:>3}  {description}");
+			}
+		}
+	} else {
+		let applied = db.migrate()?;
+		if applied.is_empty() {
+			println!("Database is up to date.");
+		}
+		for (version, description) in applied {
+			println!("Applied {version:>3}  {description}");
+		}
+	}
+	Ok(())
+}
+
+/// Handles the doctor command - reports problems and, with `--fix`, repairs
+/// what can be repaired
+pub fn handle_doctor(db: &Database, fix: bool) -> Result<()> {
+	let report = db.check_health()?;
+	print_report(&report);
+
+	if fix {
+		if report.fts_out_of_sync() {
+			println!("Rebuilt the search index ({} notes).", db.rebuild_search_index()?);
+		}
+		db.vacuum()?;
+		println!("Vacuumed and optimized the database.");
+
+		let remaining = db.check_health()?;
+		if remaining.problem_count() > 0 {
+			println!();
+			print_report(&remaining);
+			anyhow::bail!("{} problem(s) need repairing by hand", remaining.problem_count());
+		}
+		println!("No problems left.");
+	} else if report.problem_count() > 0 {
+		anyhow::bail!("Found {} problem(s); run `qnote doctor --fix` to repair them", report.problem_count());
+	}
+	Ok(())
+}
+
+/// Prints one line per check, followed by the details of any failures
+fn print_report(report: &HealthReport) {
+	let status = |count: usize, what: &str| if count == 0 { "ok".to_string() } else { format!("{count} {what}") };
+
+	println!("Integrity check:  {}", status(report.integrity.len(), "problem(s)"));
+	for message in &report.integrity {
+		println!("  {message}");
+	}
+
+	if report.fts_out_of_sync() {
+		println!(
+			"Search index:     {} missing, {} stale, {} orphaned",
+			report.fts_missing.len(),
+			report.fts_stale.len(),
+			report.fts_orphaned.len()
+		);
+		for (label, ids) in
+			[("missing", &report.fts_missing), ("stale", &report.fts_stale), ("orphaned", &report.fts_orphaned)]
+		{
+			if !ids.is_empty() {
+				println!("  {label}: {}", ids.iter().map(ToString::to_string).collect::<Vec<_>>().join(", "));
+			}
+		}
+	} else {
+		println!("Search index:     ok");
+	}
+
+	println!("Timestamps:       {}", status(report.bad_timestamps.len(), "unreadable"));
+	for bad in &report.bad_timestamps {
+		match &bad.original {
+			Some(original) => println!("  {} {} {}: {:?} (was {original:?})", bad.table, bad.id, bad.column, bad.value),
+			None => println!("  {} {} {}: {:?}", bad.table, bad.id, bad.column, bad.value),
+		}
+	}
+
+	println!("Note tags:        {}", status(report.bad_note_tags.len(), "unreadable"));
+	for (id, value) in &report.bad_note_tags {
+		println!("  note {id}: {value:?}");
+	}
+
+	println!("Revision tags:    {}", status(report.bad_tag_json.len(), "malformed"));
+	if !report.bad_tag_json.is_empty() {
+		println!("  revisions: {}", report.bad_tag_json.iter().map(ToString::to_string).collect::<Vec<_>>().join(", "));
+	}
+}
@@ -0,0 +1,49 @@
+use std::path::Path;
+
+use anyhow::Result;
+use qnote::db::{self, Database};
+
+use super::interaction::{read_new_passphrase, read_passphrase};
+use crate::settings::Config;
+
+/// Opens the database, asking for the passphrase if it is an encrypted vault.
+/// Pending migrations are applied unless `migrate` is false.
+pub fn open_database(path: &str, config: &Config, migrate: bool) -> Result<Database> {
+	if db::is_encrypted(Path::new(path))? {
+		let passphrase = read_passphrase()?;
+		if migrate {
+			Database::new_encrypted(path, &passphrase, &config.database)
+		} else {
+			Database::open_encrypted(path, &passphrase, &config.database)
+		}
+	} else if migrate {
+		Database::new(path, &config.database)
+	} else {
+		Database::open(path, &config.database)
+	}
+}
+
+/// Handles the encrypt command - encrypts the vault, or changes the
+/// passphrase of an encrypted one
+pub fn handle_encrypt(db: Database, path: &str) -> Result<()> {
+	let rekey = db.is_encrypted();
+	let passphrase = read_new_passphrase()?;
+	db.encrypt(path, &passphrase)?;
+
+	if rekey {
+		println!("Passphrase changed.");
+	} else {
+		println!("Vault encrypted. Keep the passphrase safe: the notes cannot be recovered without it.");
+	}
+	Ok(())
+}
+
+/// Handles the decrypt command - stores an encrypted vault in plain text
+pub fn handle_decrypt(db: Database, path: &str) -> Result<()> {
+	if !db.is_encrypted() {
+		anyhow::bail!("The vault is not encrypted");
+	}
+	db.decrypt(path)?;
+	println!("Vault decrypted; notes are stored in plain text again.");
+	Ok(())
+}
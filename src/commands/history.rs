@@ -0,0 +1,83 @@
+use anyhow::Result;
+use qnote::{db::{Database, Note, Revision}, utils::{format_date_full, note_to_markdown_body, properties_to_frontmatter}};
+use similar::TextDiff;
+
+use super::interaction::resolve_note;
+
+/// Handles the history command - lists a note's previous versions
+pub fn handle_history(db: &Database, id_or_title: &str) -> Result<()> {
+	let (id, note) = load_note(db, id_or_title)?;
+	let revisions = db.list_revisions(id)?;
+
+	if revisions.is_empty() {
+		println!("No previous versions of [{id}] {}.", note.title);
+	} else {
+		println!("History of [{id}] {}:", note.title);
+		println!("  {:>8}  {}  {}", "current", format_date_full(&note.updated_at), note.title);
+		for revision in revisions {
+			println!("  {:>8}  {}  {}", revision.id, format_date_full(&revision.saved_at), revision.title);
+		}
+	}
+	Ok(())
+}
+
+/// Handles the diff command - shows a unified diff between two versions
+pub fn handle_diff(db: &Database, id_or_title: &str, from: i64, to: Option<i64>) -> Result<()> {
+	let (id, note) = load_note(db, id_or_title)?;
+	let old = get_revision(db, id, from)?;
+	let (new_label, new_text) = match to {
+		Some(to) => {
+			let revision = get_revision(db, id, to)?;
+			(revision_label(&revision), diff_text(&revision.to_note()))
+		}
+		None => (format!("current ({})", format_date_full(&note.updated_at)), diff_text(&note)),
+	};
+
+	let old_text = diff_text(&old.to_note());
+	let diff = TextDiff::from_lines(&old_text, &new_text);
+	if diff.ratio() >= 1.0 {
+		println!("No differences.");
+	} else {
+		print!("{}", diff.unified_diff().header(&revision_label(&old), &new_label));
+	}
+	Ok(())
+}
+
+/// Handles the restore command - brings back a previous version of a note
+pub fn handle_restore(db: &Database, id_or_title: &str, revision_id: i64) -> Result<()> {
+	let (id, note) = load_note(db, id_or_title)?;
+	let revision = get_revision(db, id, revision_id)?;
+
+	// The version being replaced is kept as a new revision, so this can be undone
+	db.update_note(
+		id,
+		Some(note.updated_at),
+		&revision.title,
+		&revision.content,
+		&revision.tags,
+		revision.properties.as_ref(),
+	)?;
+	println!("Note {id} restored to revision {revision_id}.");
+	Ok(())
+}
+
+fn load_note(db: &Database, id_or_title: &str) -> Result<(i64, Note)> {
+	let id = resolve_note(db, id_or_title)?;
+	let note = db.get_note(id)?.ok_or_else(|| anyhow::anyhow!("Note with ID {id} not found"))?;
+	Ok((id, note))
+}
+
+fn get_revision(db: &Database, note_id: i64, revision_id: i64) -> Result<Revision> {
+	db.get_revision(note_id, revision_id)?
+		.ok_or_else(|| anyhow::anyhow!("Revision {revision_id} not found for note {note_id}"))
+}
+
+/// Markdown form of a note with its properties, newline-terminated so diffs
+/// don't flag the last line as missing one.
+fn diff_text(note: &Note) -> String {
+	properties_to_frontmatter(&note.properties) + &note_to_markdown_body(note) + "\n"
+}
+
+fn revision_label(revision: &Revision) -> String {
+	format!("revision {} ({})", revision.id, format_date_full(&revision.saved_at))
+}
@@ -0,0 +1,95 @@
+//! User interaction utilities for the command handlers.
+
+use std::{env, io::{Write, stdin, stdout}};
+
+use anyhow::{Context, Result};
+use qnote::db::{Database, NoteSummary};
+
+/// Prompts user for confirmation. Returns true if user confirms.
+///
+/// # Examples
+/// ```
+/// if confirm("Delete this note?") {
+///     // User confirmed
+/// }
+/// ```
+pub fn confirm(prompt: &str) -> bool {
+	print!("{prompt} (y/N): ");
+	stdout().flush().ok();
+	let mut input = String::new();
+	stdin().read_line(&mut input).ok();
+	matches!(input.trim(), "y" | "Y" | "yes" | "Yes")
+}
+
+/// Environment variable holding the passphrase of an encrypted vault, for
+/// scripts that cannot answer a prompt.
+const PASSPHRASE_ENV_VAR: &str = "QNOTE_PASSPHRASE";
+
+/// Environment variable holding the passphrase to set with `qnote encrypt`.
+const NEW_PASSPHRASE_ENV_VAR: &str = "QNOTE_NEW_PASSPHRASE";
+
+/// Reads the passphrase of an encrypted vault from `$QNOTE_PASSPHRASE`, or
+/// asks for it without echoing.
+pub fn read_passphrase() -> Result<String> {
+	match env::var(PASSPHRASE_ENV_VAR) {
+		Ok(passphrase) => Ok(passphrase),
+		Err(_) => rpassword::prompt_password("Vault passphrase: ").context("Failed to read passphrase"),
+	}
+}
+
+/// Reads a new passphrase from `$QNOTE_NEW_PASSPHRASE`, or asks for it twice
+/// without echoing.
+pub fn read_new_passphrase() -> Result<String> {
+	let passphrase = match env::var(NEW_PASSPHRASE_ENV_VAR) {
+		Ok(passphrase) => passphrase,
+		Err(_) => {
+			let passphrase = rpassword::prompt_password("New passphrase: ").context("Failed to read passphrase")?;
+			if rpassword::prompt_password("Repeat passphrase: ").context("Failed to read passphrase")? != passphrase {
+				anyhow::bail!("Passphrases do not match");
+			}
+			passphrase
+		}
+	};
+	if passphrase.is_empty() {
+		anyhow::bail!("The passphrase must not be empty");
+	}
+	Ok(passphrase)
+}
+
+/// Resolves a note by ID or title pattern.
+/// Returns the note ID if found, or an error if ambiguous/not found.
+///
+/// This function supports flexible note identification:
+/// - Direct numeric ID: "42" -> finds note with ID 42
+/// - Title pattern (case-insensitive): "groceries" -> finds notes containing
+///   "groceries"
+///
+/// If multiple notes match a title pattern, returns an error with suggestions.
+pub fn resolve_note(db: &Database, id_or_title: &str) -> Result<i64> {
+	// Try parsing as ID first
+	if let Ok(id) = id_or_title.parse::<i64>() {
+		// Verify the ID exists and is not in the trash
+		match db.get_note(id)? {
+			Some(note) if note.deleted_at.is_some() => {
+				anyhow::bail!("Note {id} is in the trash (restore it with `qnote trash restore {id}`)")
+			}
+			Some(_) => return Ok(id),
+			None => anyhow::bail!("Note with ID {id} not found"),
+		}
+	}
+
+	// Search by title pattern (case-insensitive)
+	let matches: Vec<NoteSummary> = db.find_by_title(id_or_title)?;
+
+	match matches.len() {
+		0 => anyhow::bail!("No notes found matching '{id_or_title}'"),
+		1 => Ok(matches[0].id),
+		_ => {
+			eprintln!("Multiple notes found matching '{id_or_title}':");
+			for note in &matches {
+				eprintln!("  [{}] {}", note.id, note.title);
+			}
+			anyhow::bail!("Please specify a more specific pattern or use the exact ID")
+		}
+	}
+}
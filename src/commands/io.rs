@@ -1,44 +1,113 @@
-use std::{fs, path::Path};
+use std::{collections::HashSet, fs, path::Path};
 
 use anyhow::Result;
+use qnote::{db::{Database, ImportOutcome}, utils::{note_to_markdown, parse_markdown_file, sanitize_filename}};
 
-use crate::{db::{Database, Note}, utils::{note_to_markdown, parse_markdown_file, resolve_note, sanitize_filename}};
+use super::{interaction::resolve_note, note_ops::parse_note_dates};
+use crate::cli::DateArgs;
 
-/// Handles the export command - exports a note to markdown file
-pub fn handle_export(db: &Database, id_or_title: &str, output: Option<String>) -> Result<()> {
+/// Handles the export command - exports a note to markdown file, or to a
+/// directory together with its attachments
+pub fn handle_export(db: &Database, id_or_title: &str, output: Option<String>, dir: Option<String>) -> Result<()> {
 	let id = resolve_note(db, id_or_title)?;
 	if let Some(note) = db.get_note(id)? {
-		let filename = output.unwrap_or_else(|| format!("{}.md", sanitize_filename(&note.title)));
+		let name = sanitize_filename(&note.title);
+		let filename = match (&dir, output) {
+			(Some(dir), _) => {
+				fs::create_dir_all(dir)?;
+				Path::new(dir).join(format!("{name}.md")).display().to_string()
+			}
+			(None, Some(output)) => output,
+			(None, None) => format!("{name}.md"),
+		};
 		let content = note_to_markdown(&note);
 
 		fs::write(&filename, content)?;
 		println!("Exported to: {filename}");
+
+		if let Some(dir) = dir {
+			export_attachments(db, id, &Path::new(&dir).join(name))?;
+		}
 	}
 	Ok(())
 }
 
+/// Writes a note's attachments into `dir`, prefixing repeated filenames with
+/// the attachment ID.
+fn export_attachments(db: &Database, note_id: i64, dir: &Path) -> Result<()> {
+	let attachments = db.list_attachments(note_id)?;
+	if attachments.is_empty() {
+		return Ok(());
+	}
+
+	fs::create_dir_all(dir)?;
+	let mut used = HashSet::new();
+	for attachment in &attachments {
+		let filename = if used.insert(attachment.filename.clone()) {
+			attachment.filename.clone()
+		} else {
+			format!("{}-{}", attachment.id, attachment.filename)
+		};
+		fs::write(dir.join(&filename), db.attachment_data(attachment.id)?)?;
+	}
+	println!("Exported {} attachment(s) to: {}", attachments.len(), dir.display());
+	Ok(())
+}
+
 /// Handles the import command - imports notes from markdown files
-pub fn handle_import(db: &Database, files: &[String]) -> Result<()> {
-	let mut imported = 0;
+///
+/// Files are read and parsed first, then every note is written in a single
+/// transaction. Unreadable files are skipped with a warning, or abort the
+/// whole import if `atomic` is set.
+pub fn handle_import(db: &Database, files: &[String], atomic: bool, dates: &DateArgs) -> Result<()> {
+	let dates = parse_note_dates(dates)?;
+	let mut parsed_files = Vec::with_capacity(files.len());
+	let mut failed = 0;
 	for file_path in files {
-		let path = Path::new(file_path);
-		if !path.exists() {
-			eprintln!("Warning: File not found: {file_path}");
-			continue;
+		let parsed = fs::read_to_string(file_path)
+			.map_err(|e| format!("Could not read {file_path}: {e}"))
+			.and_then(|content| parse_markdown_file(&content).ok_or_else(|| format!("Could not parse: {file_path}")));
+		match parsed {
+			Ok(parsed) => parsed_files.push((file_path, parsed)),
+			Err(msg) if atomic => anyhow::bail!("{msg} (nothing imported)"),
+			Err(msg) => {
+				eprintln!("Warning: {msg}");
+				failed += 1;
+			}
 		}
+	}
 
-		let content = fs::read_to_string(path)?;
+	let (paths, notes): (Vec<_>, Vec<_>) = parsed_files
+		.into_iter()
+		.map(|(path, parsed)| {
+			let mut note = parsed.into_note();
+			// Only applies to new notes; notes matched by UUID keep their dates
+			if let Some((created, updated)) = dates {
+				(note.created_at, note.updated_at) = (created, updated);
+			}
+			(path, note)
+		})
+		.unzip();
 
-		if let Some((title, note_content, tags)) = parse_markdown_file(&content) {
-			let note = Note::new(title, note_content, tags);
-			db.create_note(&note)?;
-			imported += 1;
-			let display_path = path.display();
-			println!("Imported: {display_path}");
-		} else {
-			eprintln!("Warning: Could not parse: {file_path}");
+	let (mut imported, mut updated) = (0, 0);
+	for (path, outcome) in paths.iter().zip(db.import_notes(&notes)?) {
+		match outcome {
+			ImportOutcome::Created(_) => {
+				imported += 1;
+				println!("Imported: {path}");
+			}
+			ImportOutcome::Updated(id) => {
+				updated += 1;
+				println!("Updated: {path} (note {id})");
+			}
+			ImportOutcome::Unchanged(_) => println!("Unchanged: {path}"),
 		}
 	}
-	println!("\nImported {imported} note(s)");
+
+	if failed > 0 {
+		println!("\nImported {imported} note(s), updated {updated}, skipped {failed} file(s)");
+	} else {
+		println!("\nImported {imported} note(s), updated {updated}");
+	}
 	Ok(())
 }
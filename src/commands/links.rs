@@ -0,0 +1,39 @@
+use anyhow::Result;
+use qnote::db::Database;
+
+use super::interaction::resolve_note;
+
+/// Handles the links command - lists a note's outgoing wikilinks
+pub fn handle_links(db: &Database, id_or_title: &str) -> Result<()> {
+	let id = resolve_note(db, id_or_title)?;
+	let links = db.links_from(id)?;
+
+	if links.is_empty() {
+		println!("Note {id} has no links.");
+	} else {
+		println!("Links from note {id}:");
+		for link in links {
+			match link.target_id {
+				Some(target_id) => println!("  [{target_id}] {}", link.target_title),
+				None => println!("  [-] {} (no such note)", link.target_title),
+			}
+		}
+	}
+	Ok(())
+}
+
+/// Handles the backlinks command - lists notes that link to a note
+pub fn handle_backlinks(db: &Database, id_or_title: &str) -> Result<()> {
+	let id = resolve_note(db, id_or_title)?;
+	let notes = db.backlinks(id)?;
+
+	if notes.is_empty() {
+		println!("No notes link to note {id}.");
+	} else {
+		println!("Notes linking to note {id}:");
+		for note in notes {
+			println!("  [{}] {}", note.id, note.title);
+		}
+	}
+	Ok(())
+}
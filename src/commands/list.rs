@@ -1,97 +1,67 @@
-use std::collections::HashSet;
-
 use anyhow::Result;
+use qnote::{db::{Database, NoteQuery, NoteSummary, SortKey}, utils::{format_date_full, format_date_only, format_size, parse_date, parse_property_filter, parse_tags}};
 
-use crate::{cli::SortBy, db::{Database, Note}, utils::{format_date_full, format_date_only}};
-
-/// Handles the list command - displays all notes with optional filtering
-pub fn handle_list(
-	db: &Database,
-	tag: Option<String>,
-	oneline: bool,
-	sort: SortBy,
-	limit: Option<usize>,
-) -> Result<()> {
-	let notes = db.list_notes()?;
-
-	let mut filtered: Vec<Note> = if let Some(tag_filter) = tag {
-		notes.into_iter().filter(|n| n.tags.contains(&tag_filter)).collect()
-	} else {
-		notes
-	};
-
-	match sort {
-		SortBy::Created => filtered.sort_by(|a, b| b.created_at.cmp(&a.created_at)),
-		SortBy::Title => {
-			filtered.sort_by(|a, b| a.title.to_lowercase().cmp(&b.title.to_lowercase()));
-		}
-		SortBy::Updated => filtered.sort_by(|a, b| b.updated_at.cmp(&a.updated_at)),
-	}
+use super::notebooks::resolve_notebook;
+use crate::cli::{ListArgs, SortBy};
 
-	if let Some(limit_val) = limit {
-		filtered.truncate(limit_val);
-	}
+/// Handles the list command - displays notes matching the given filters
+pub fn handle_list(db: &Database, args: &ListArgs) -> Result<()> {
+	let notes = db.query_notes(&list_query(db, args)?)?;
 
-	if filtered.is_empty() {
+	if notes.is_empty() {
 		println!("No notes found.");
-	} else if oneline {
-		print_notes_oneline(&filtered);
+	} else if args.oneline {
+		print_notes_oneline(&notes);
 	} else {
-		print_notes_normal(&filtered);
+		print_notes_normal(&notes);
 	}
 	Ok(())
 }
 
-/// Handles the tags command - lists all tags with note counts
-pub fn handle_tags(db: &Database) -> Result<()> {
-	let notes = db.list_notes()?;
-
-	// Pre-allocate HashMap capacity
-	let estimated_capacity = notes.iter().map(|n| n.tags.len()).sum();
-	let mut tag_counts: std::collections::HashMap<String, usize> =
-		std::collections::HashMap::with_capacity(estimated_capacity);
+/// Builds the database query for the list command's arguments.
+fn list_query(db: &Database, args: &ListArgs) -> Result<NoteQuery> {
+	let date = |arg: &Option<String>, end_of_day| arg.as_deref().map(|d| parse_date(d, end_of_day)).transpose();
 
-	for note in notes {
-		for tag in note.tags {
-			*tag_counts.entry(tag).or_insert(0) += 1;
-		}
+	let tags = parse_tags(args.tag.clone());
+	let mut query = if args.all_tags { NoteQuery::new().tags_all(tags) } else { NoteQuery::new().tags_any(tags) };
+	if let Some(text) = &args.r#match {
+		query = query.text(text.clone());
+	}
+	if let Some(path) = &args.notebook {
+		query = query.notebook(Some(resolve_notebook(db, path)?));
+	}
+	for input in &args.r#where {
+		let (key, value) = parse_property_filter(input)?;
+		query = query.property(key, value);
 	}
 
-	if tag_counts.is_empty() {
-		println!("No tags found.");
-	} else {
-		let mut tags: Vec<(String, usize)> = tag_counts.into_iter().collect();
-		tags.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+	// Dates sort newest first and titles A→Z unless reversed
+	let (key, descending) = match args.sort {
+		SortBy::Updated => (SortKey::Updated, true),
+		SortBy::Created => (SortKey::Created, true),
+		SortBy::Title => (SortKey::Title, false),
+	};
 
-		let total = tags.len();
-		println!("Tags ({total} total):");
-		for (tag, count) in tags {
-			println!("  {tag} ({count})");
-		}
-	}
-	Ok(())
+	Ok(
+		query
+			.updated_between(date(&args.since, false)?, date(&args.until, true)?)
+			.created_between(date(&args.created_since, false)?, date(&args.created_until, true)?)
+			.include_archived(args.archived)
+			.sort(key, descending != args.reverse)
+			.limit(args.limit)
+			.offset(args.offset),
+	)
 }
 
 /// Handles the stats command - shows note statistics
 pub fn handle_stats(db: &Database) -> Result<()> {
-	let notes = db.list_notes()?;
-	if notes.is_empty() {
+	let stats = db.stats()?;
+	let (Some((oldest_title, oldest_date)), Some((newest_title, newest_date))) = (stats.oldest, stats.newest) else {
 		println!("No notes yet!");
 		return Ok(());
-	}
-
-	let (total_size, tag_set, oldest, newest) =
-		notes.iter().fold((0, HashSet::new(), &notes[0], &notes[0]), |(size, mut tags, old, new), note| {
-			tags.extend(note.tags.iter().cloned());
-			(
-				size + note.content.len() + note.title.len(),
-				tags,
-				if note.created_at < old.created_at { note } else { old },
-				if note.updated_at > new.updated_at { note } else { new },
-			)
-		});
+	};
 
-	let size_kb = total_size as f64 / 1024.0;
+	let size_kb = stats.total_bytes as f64 / 1024.0;
 	let sep = "=".repeat(50);
 	println!(
 		"\n{sep}\nqnote Statistics\n{sep}\n\
@@ -100,35 +70,47 @@ pub fn handle_stats(db: &Database) -> Result<()> {
         Total size:       {:.2} KB\n\
         Oldest note:      {} ({})\n\
         Most recent:      {} ({})\n{sep}",
-		notes.len(),
-		tag_set.len(),
+		stats.note_count,
+		stats.tag_count,
 		size_kb,
-		oldest.title,
-		format_date_only(&oldest.created_at),
-		newest.title,
-		format_date_full(&newest.updated_at)
+		oldest_title,
+		format_date_only(&oldest_date),
+		newest_title,
+		format_date_full(&newest_date)
 	);
 	Ok(())
 }
 
-fn print_notes_oneline(notes: &[Note]) {
+fn print_notes_oneline(notes: &[NoteSummary]) {
 	for note in notes {
 		let tags_str = if note.tags.is_empty() { String::new() } else { format!(" [{}]", note.tags.join(", ")) };
-		if let Some(id) = note.id {
-			println!("{id}\t{}{tags_str}", note.title);
-		}
+		println!("{}\t{}{}{tags_str}", note.id, note.title, state_suffix(note));
 	}
 }
 
-fn print_notes_normal(notes: &[Note]) {
+fn print_notes_normal(notes: &[NoteSummary]) {
 	for note in notes {
-		if let Some(id) = note.id {
-			println!(
-				"\n[{id}] {}\nTags: {}\nUpdated: {}",
-				note.title,
-				note.tags.join(", "),
-				format_date_full(&note.updated_at)
-			);
+		println!(
+			"\n[{}] {}{}\nTags: {}\nUpdated: {} ({})",
+			note.id,
+			note.title,
+			state_suffix(note),
+			note.tags.join(", "),
+			format_date_full(&note.updated_at),
+			format_size(note.size)
+		);
+		if !note.first_line.is_empty() {
+			println!("  {}", note.first_line);
 		}
 	}
 }
+
+/// Marks pinned and archived notes in listings, e.g. " (pinned)".
+pub(super) fn state_suffix(note: &NoteSummary) -> &'static str {
+	match (note.pinned, note.archived) {
+		(true, true) => " (pinned, archived)",
+		(true, false) => " (pinned)",
+		(false, true) => " (archived)",
+		(false, false) => "",
+	}
+}
@@ -1,30 +1,86 @@
+mod attachments;
+mod backup;
 mod config;
+mod db;
+mod encryption;
+mod history;
+mod interaction;
 mod io;
+mod links;
 mod list;
 mod note_ops;
+mod notebooks;
+mod properties;
+mod states;
+mod tags;
+mod tasks;
+mod trash;
+mod vault;
 
 use anyhow::Result;
+pub use attachments::{handle_attach, handle_attachments, handle_detach, handle_extract};
+pub use backup::{auto_backup, handle_backup, handle_restore_backup};
 pub use config::handle_config;
+pub use db::{handle_db, handle_doctor};
+pub use encryption::{handle_decrypt, handle_encrypt, open_database};
+pub use history::{handle_diff, handle_history, handle_restore};
 pub use io::{handle_export, handle_import};
-pub use list::{handle_list, handle_stats, handle_tags};
+pub use links::{handle_backlinks, handle_links};
+pub use list::{handle_list, handle_stats};
 pub use note_ops::{handle_add, handle_delete, handle_edit, handle_search, handle_show};
+pub use notebooks::handle_notebook;
+pub use properties::handle_prop;
+use qnote::db::Database;
+pub use states::{handle_archive, handle_pin};
+pub use tags::{handle_tag, handle_tags};
+pub use tasks::{handle_task, handle_tasks};
+pub use trash::handle_trash;
+pub use vault::handle_vault;
 
-use crate::{cli::Commands, db::Database};
+use crate::cli::Commands;
 
 /// Dispatches CLI commands to their respective handlers
 pub fn handle_command(db: &Database, cmd: Commands) -> Result<()> {
 	match cmd {
-		Commands::Add { title, content, tags } => handle_add(db, title, content, tags),
-		Commands::List { tag, oneline, sort, limit } => handle_list(db, tag, oneline, sort, limit),
+		Commands::Add { title, content, tags, notebook, dates } => {
+			handle_add(db, title, content, tags, notebook.as_deref(), &dates)
+		}
+		Commands::List(args) => handle_list(db, &args),
 		Commands::Show { id_or_title } => handle_show(db, &id_or_title),
 		Commands::Edit { id_or_title, title, content, tags } => handle_edit(db, &id_or_title, title, content, tags),
 		Commands::Delete { id_or_title, yes } => handle_delete(db, &id_or_title, yes),
-		Commands::Search { query } => handle_search(db, &query),
-		Commands::Export { id_or_title, output } => handle_export(db, &id_or_title, output),
-		Commands::Import { files } => handle_import(db, &files),
-		Commands::Tags => handle_tags(db),
+		Commands::Pin { id_or_title } => handle_pin(db, &id_or_title, true),
+		Commands::Unpin { id_or_title } => handle_pin(db, &id_or_title, false),
+		Commands::Archive { id_or_title } => handle_archive(db, &id_or_title, true),
+		Commands::Unarchive { id_or_title } => handle_archive(db, &id_or_title, false),
+		Commands::Links { id_or_title } => handle_links(db, &id_or_title),
+		Commands::Backlinks { id_or_title } => handle_backlinks(db, &id_or_title),
+		Commands::Attach { id_or_title, file } => handle_attach(db, &id_or_title, &file),
+		Commands::Attachments { id_or_title } => handle_attachments(db, &id_or_title),
+		Commands::Detach { attachment_id } => handle_detach(db, attachment_id),
+		Commands::Extract { attachment_id, output } => handle_extract(db, attachment_id, output),
+		Commands::History { id_or_title } => handle_history(db, &id_or_title),
+		Commands::Diff { id_or_title, from, to } => handle_diff(db, &id_or_title, from, to),
+		Commands::Restore { id_or_title, revision } => handle_restore(db, &id_or_title, revision),
+		Commands::Search { query, r#where, notebook, archived } => {
+			handle_search(db, &query, &r#where, notebook.as_deref(), archived)
+		}
+		Commands::Export { id_or_title, output, dir } => handle_export(db, &id_or_title, output, dir),
+		Commands::Import { files, atomic, dates } => handle_import(db, &files, atomic, &dates),
+		Commands::Tags { tree } => handle_tags(db, tree),
+		Commands::Tag { command } => handle_tag(db, command),
+		Commands::Notebook { command } => handle_notebook(db, command),
+		Commands::Prop { command } => handle_prop(db, command),
+		Commands::Tasks { open, tag, archived } => handle_tasks(db, open, tag, archived),
+		Commands::Task { command } => handle_task(db, command),
 		Commands::Stats => handle_stats(db),
+		Commands::Trash { command } => handle_trash(db, command),
 		Commands::Tui => Ok(()), // Never reached - TUI is handled in main.rs
 		Commands::Config { show } => handle_config(show),
+		Commands::Db { command } => handle_db(db, command),
+		Commands::Doctor { fix } => handle_doctor(db, fix),
+		Commands::Vault { .. } => Ok(()), // Handled in main.rs before the database is opened
+		Commands::Encrypt | Commands::Decrypt => Ok(()), // Handled in main.rs, which hands over the database
+		Commands::Backup { .. } | Commands::RestoreBackup { .. } => Ok(()), // Handled in main.rs, which has the path
 	}
 }
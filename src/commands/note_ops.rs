@@ -1,22 +1,59 @@
 use anyhow::Result;
+use chrono::{DateTime, Utc};
+use qnote::{db::{Database, Note, NoteQuery, SearchResult}, utils::{format_date_full, parse_date, parse_property_filter, parse_tags}};
 
-use crate::{db::{Database, Note}, utils::{confirm, format_date_full, parse_tags, resolve_note}};
+use super::{interaction::{confirm, resolve_note}, list::state_suffix, notebooks::resolve_notebook};
+use crate::cli::DateArgs;
 
 /// Handles the add command - creates a new note
-pub fn handle_add(db: &Database, title: String, content: String, tags: Option<String>) -> Result<()> {
+pub fn handle_add(
+	db: &Database,
+	title: String,
+	content: String,
+	tags: Option<String>,
+	notebook: Option<&str>,
+	dates: &DateArgs,
+) -> Result<()> {
 	let tag_vec = parse_tags(tags);
-	let note = Note::new(title, content, tag_vec);
+	let mut note = Note::new(title, content, tag_vec);
+	note.notebook_id = notebook.map(|path| resolve_notebook(db, path)).transpose()?;
+	if let Some((created, updated)) = parse_note_dates(dates)? {
+		(note.created_at, note.updated_at) = (created, updated);
+	}
 	let id = db.create_note(&note)?;
 	println!("Note created with ID: {id}");
 	Ok(())
 }
 
+/// Parses `--created`/`--updated` into (created, updated), or None if neither
+/// is given. Either date defaults to the other; a note cannot be updated
+/// before it was created.
+pub(super) fn parse_note_dates(dates: &DateArgs) -> Result<Option<(DateTime<Utc>, DateTime<Utc>)>> {
+	let created = dates.created.as_deref().map(|date| parse_date(date, false)).transpose()?;
+	let updated = dates.updated.as_deref().map(|date| parse_date(date, false)).transpose()?;
+	let (created, updated) = match (created, updated) {
+		(None, None) => return Ok(None),
+		(Some(created), None) => (created, created),
+		(None, Some(updated)) => (updated, updated),
+		(Some(created), Some(updated)) => (created, updated),
+	};
+	if updated < created {
+		anyhow::bail!("--updated must not be earlier than --created");
+	}
+	Ok(Some((created, updated)))
+}
+
 /// Handles the show command - displays a specific note
 pub fn handle_show(db: &Database, id_or_title: &str) -> Result<()> {
 	if let Some(note) = db.get_note(resolve_note(db, id_or_title)?)? {
 		let sep = "=".repeat(50);
+		let properties: String = note.properties.iter().map(|(key, value)| format!("{key}: {value}\n")).collect();
+		let notebook = match note.notebook_id {
+			Some(id) => db.notebook_path(id)?.map(|path| format!("Notebook: {path}\n")).unwrap_or_default(),
+			None => String::new(),
+		};
 		println!(
-			"\n{sep}\nTitle: {}\nTags: {}\nCreated: {}\nUpdated: {}\n{sep}\n\n{}\n",
+			"\n{sep}\nTitle: {}\n{notebook}Tags: {}\nCreated: {}\nUpdated: {}\n{properties}{sep}\n\n{}\n",
 			note.title,
 			note.tags.join(", "),
 			format_date_full(&note.created_at),
@@ -41,20 +78,20 @@ pub fn handle_edit(
 		let new_content = content.unwrap_or(note.content);
 		let new_tags = tags.map(|t| parse_tags(Some(t))).unwrap_or(note.tags);
 
-		db.update_note(id, &new_title, &new_content, &new_tags)?;
+		db.update_note(id, Some(note.updated_at), &new_title, &new_content, &new_tags, None)?;
 		println!("Note {id} updated.");
 	}
 	Ok(())
 }
 
-/// Handles the delete command - removes a note
+/// Handles the delete command - moves a note to the trash
 pub fn handle_delete(db: &Database, id_or_title: &str, yes: bool) -> Result<()> {
 	let id = resolve_note(db, id_or_title)?;
 	if let Some(note) = db.get_note(id)? {
 		println!("Found: [{}] {}", id, note.title);
-		if yes || confirm("Delete this note?") {
-			db.delete_note(id)?;
-			println!("Note {id} deleted.");
+		if yes || confirm("Move this note to the trash?") {
+			db.trash_note(id)?;
+			println!("Note {id} moved to trash.");
 		} else {
 			println!("Deletion cancelled.");
 		}
@@ -62,16 +99,30 @@ pub fn handle_delete(db: &Database, id_or_title: &str, yes: bool) -> Result<()>
 	Ok(())
 }
 
-/// Handles the search command - finds notes by keyword
-pub fn handle_search(db: &Database, query: &str) -> Result<()> {
-	let notes = db.search_notes(query)?;
-	if notes.is_empty() {
+/// Handles the search command - finds notes by keyword, best matches first
+pub fn handle_search(
+	db: &Database,
+	query: &str,
+	filters: &[String],
+	notebook: Option<&str>,
+	archived: bool,
+) -> Result<()> {
+	let notebook = notebook.map(|path| resolve_notebook(db, path)).transpose()?;
+	let mut filter = NoteQuery::new().include_archived(archived).notebook(notebook);
+	for input in filters {
+		let (key, value) = parse_property_filter(input)?;
+		filter = filter.property(key, value);
+	}
+	let results = db.search_notes(query, &filter)?;
+	if results.is_empty() {
 		println!("No notes found matching '{query}'.");
 	} else {
-		println!("Found {} note(s):", notes.len());
-		for note in notes {
-			if let Some(id) = note.id {
-				println!("\n[{id}] {}\nTags: {}", note.title, note.tags.join(", "));
+		println!("Found {} note(s):", results.len());
+		for SearchResult { note, snippet, .. } in results {
+			println!("\n[{}] {}{}\nTags: {}", note.id, note.title, state_suffix(&note), note.tags.join(", "));
+			let snippet = snippet.split_whitespace().collect::<Vec<_>>().join(" ");
+			if !snippet.is_empty() {
+				println!("  {snippet}");
 			}
 		}
 	}
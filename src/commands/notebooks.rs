@@ -0,0 +1,61 @@
+use anyhow::Result;
+use qnote::db::{Database, NotebookNode};
+
+use super::{interaction::confirm, tags::resolve_notes};
+use crate::cli::NotebookCommand;
+
+/// Handles the notebook command - creates, lists and deletes notebooks and
+/// files notes in them
+pub fn handle_notebook(db: &Database, command: NotebookCommand) -> Result<()> {
+	match command {
+		NotebookCommand::Create { path } => {
+			let id = db.create_notebook(&path)?;
+			println!("Created notebook '{}'.", db.notebook_path(id)?.unwrap_or(path));
+		}
+		NotebookCommand::List => {
+			let roots = db.notebook_tree()?;
+			if roots.is_empty() {
+				println!("No notebooks yet.");
+			}
+			for root in &roots {
+				println!("{} ({})", root.name, root.count);
+				print_notebook_children(root, "");
+			}
+		}
+		NotebookCommand::Move { notebook, note } => {
+			let ids = resolve_notes(db, &note)?;
+			let notebook_id = notebook.as_deref().map(|path| resolve_notebook(db, path)).transpose()?;
+			let count = db.move_notes_to_notebook(&ids, notebook_id)?;
+			match notebook_id.map(|id| db.notebook_path(id)).transpose()?.flatten() {
+				Some(path) => println!("Moved {count} of {} note(s) to '{path}'.", ids.len()),
+				None => println!("Took {count} of {} note(s) out of their notebook.", ids.len()),
+			}
+		}
+		NotebookCommand::Delete { path, yes } => {
+			let id = resolve_notebook(db, &path)?;
+			let path = db.notebook_path(id)?.unwrap_or(path);
+			if yes || confirm(&format!("Delete notebook '{path}' and the notebooks below it (their notes are kept)?")) {
+				let count = db.delete_notebook(id)?;
+				println!("Deleted notebook '{path}'; {count} note(s) are no longer in a notebook.");
+			} else {
+				println!("Cancelled.");
+			}
+		}
+	}
+	Ok(())
+}
+
+/// Looks up a notebook by path, failing if it does not exist.
+pub(super) fn resolve_notebook(db: &Database, path: &str) -> Result<i64> {
+	db.find_notebook(path)?
+		.ok_or_else(|| anyhow::anyhow!("Notebook '{path}' not found (create it with `qnote notebook create \"{path}\"`)"))
+}
+
+/// Prints the notebooks below `node` with box-drawing branches.
+fn print_notebook_children(node: &NotebookNode, prefix: &str) {
+	for (i, child) in node.children.iter().enumerate() {
+		let last = i + 1 == node.children.len();
+		println!("{prefix}{}{} ({})", if last { "└── " } else { "├── " }, child.name, child.count);
+		print_notebook_children(child, &format!("{prefix}{}", if last { "    " } else { "│   " }));
+	}
+}
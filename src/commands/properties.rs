@@ -0,0 +1,50 @@
+use anyhow::Result;
+use qnote::db::{Database, PropertyValue};
+
+use super::interaction::resolve_note;
+use crate::cli::PropCommand;
+
+/// Handles the prop command - sets, removes and lists note properties
+pub fn handle_prop(db: &Database, command: PropCommand) -> Result<()> {
+	match command {
+		PropCommand::Set { id_or_title, key, value } => {
+			let id = resolve_note(db, &id_or_title)?;
+			if value.contains('\n') {
+				anyhow::bail!("Property values must fit on one line");
+			}
+			let value = PropertyValue::parse(&value);
+			db.set_property(id, key.trim(), &value)?;
+			println!("Set {} = {value} ({}) on note {id}.", key.trim(), value.kind());
+		}
+		PropCommand::Unset { id_or_title, key } => {
+			let id = resolve_note(db, &id_or_title)?;
+			if db.remove_property(id, key.trim())? {
+				println!("Removed '{}' from note {id}.", key.trim());
+			} else {
+				println!("Note {id} has no property '{}'.", key.trim());
+			}
+		}
+		PropCommand::List { id_or_title: Some(id_or_title) } => {
+			let id = resolve_note(db, &id_or_title)?;
+			let properties = db.get_note(id)?.map(|note| note.properties).unwrap_or_default();
+			if properties.is_empty() {
+				println!("Note {id} has no properties.");
+			}
+			for (key, value) in properties {
+				println!("  {key}: {value} ({})", value.kind());
+			}
+		}
+		PropCommand::List { id_or_title: None } => {
+			let properties = db.property_counts()?;
+			if properties.is_empty() {
+				println!("No properties found.");
+			} else {
+				println!("Properties ({} total):", properties.len());
+				for (key, count) in properties {
+					println!("  {key} ({count})");
+				}
+			}
+		}
+	}
+	Ok(())
+}
@@ -0,0 +1,20 @@
+use anyhow::Result;
+use qnote::db::Database;
+
+use super::interaction::resolve_note;
+
+/// Handles the pin and unpin commands
+pub fn handle_pin(db: &Database, id_or_title: &str, pinned: bool) -> Result<()> {
+	let id = resolve_note(db, id_or_title)?;
+	db.set_pinned(id, pinned)?;
+	println!("Note {id} {}.", if pinned { "pinned" } else { "unpinned" });
+	Ok(())
+}
+
+/// Handles the archive and unarchive commands
+pub fn handle_archive(db: &Database, id_or_title: &str, archived: bool) -> Result<()> {
+	let id = resolve_note(db, id_or_title)?;
+	db.set_archived(id, archived)?;
+	println!("Note {id} {}.", if archived { "archived" } else { "unarchived" });
+	Ok(())
+}
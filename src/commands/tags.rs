@@ -0,0 +1,166 @@
+use anyhow::Result;
+use qnote::{db::{Database, TagNode}, utils::normalize_tag};
+
+use super::interaction::{confirm, resolve_note};
+use crate::cli::TagCommand;
+
+/// Handles the tags command - lists all tags with note counts, flat or as a
+/// tree
+pub fn handle_tags(db: &Database, tree: bool) -> Result<()> {
+	if tree {
+		let roots = db.tag_tree()?;
+		if roots.is_empty() {
+			println!("No tags found.");
+		}
+		for root in &roots {
+			println!("{} ({})", root.name, root.count);
+			print_tag_children(root, "");
+		}
+		return Ok(());
+	}
+
+	let tags = db.tag_counts()?;
+
+	if tags.is_empty() {
+		println!("No tags found.");
+	} else {
+		let total = tags.len();
+		println!("Tags ({total} total):");
+		for (tag, count) in tags {
+			println!("  {tag} ({count})");
+		}
+	}
+	Ok(())
+}
+
+/// Prints the tags below `node` with box-drawing branches.
+fn print_tag_children(node: &TagNode, prefix: &str) {
+	for (i, child) in node.children.iter().enumerate() {
+		let last = i + 1 == node.children.len();
+		println!("{prefix}{}{} ({})", if last { "└── " } else { "├── " }, child.name, child.count);
+		print_tag_children(child, &format!("{prefix}{}", if last { "    " } else { "│   " }));
+	}
+}
+
+/// Handles the tag command - edits tags across all notes, or with `dry_run`
+/// lists the notes an edit would change
+pub fn handle_tag(db: &Database, command: TagCommand) -> Result<()> {
+	match command {
+		TagCommand::Rename { old, new, dry_run } => {
+			let (old, new) = (normalize_tag(&old), normalize_tag(&new));
+			if new.is_empty() {
+				anyhow::bail!("The new tag name is empty");
+			}
+			if dry_run {
+				let sources = if old == new { Vec::new() } else { vec![old.as_str()] };
+				return preview_tagged(db, &format!("rename '{old}' to '{new}' on"), &sources);
+			}
+			let count = db.rename_tag(&old, &new)?;
+			if count == 0 {
+				println!("No notes tagged '{old}'.");
+			} else {
+				println!("Renamed '{old}' to '{new}' on {count} note(s).");
+			}
+		}
+		TagCommand::Merge { sources, into, dry_run } => {
+			let sources: Vec<String> = sources.iter().map(|tag| normalize_tag(tag)).collect();
+			let into = normalize_tag(&into);
+			if into.is_empty() {
+				anyhow::bail!("The tag to merge into is empty");
+			}
+			if dry_run {
+				// Merging a tag into itself changes nothing, as in merge_tags
+				let changed: Vec<&str> = sources.iter().map(String::as_str).filter(|&source| source != into).collect();
+				return preview_tagged(db, &format!("merge {} into '{into}' on", quoted_list(&sources)), &changed);
+			}
+			let count = db.merge_tags(&sources.iter().map(String::as_str).collect::<Vec<_>>(), &into)?;
+			println!("Merged {} into '{into}' on {count} note(s).", quoted_list(&sources));
+		}
+		TagCommand::Delete { tag, yes, dry_run } => {
+			let tag = normalize_tag(&tag);
+			let tags = db.tags_under(&tag)?;
+			if tags.is_empty() {
+				println!("No tag '{tag}'.");
+			} else if dry_run {
+				preview_tagged(db, &format!("remove {} from", quoted_list(&tags)), &[tag.as_str()])?;
+			} else if yes || confirm(&format!("Remove {} from every note?", quoted_list(&tags))) {
+				let count = db.delete_tag(&tag)?;
+				println!("Removed {} from {count} note(s).", quoted_list(&tags));
+			} else {
+				println!("Cancelled.");
+			}
+		}
+		TagCommand::Add { tag, note, dry_run } => {
+			let ids = resolve_notes(db, &note)?;
+			let tag = normalize_tag(&tag);
+			if tag.is_empty() {
+				anyhow::bail!("The tag name is empty");
+			}
+			if dry_run {
+				let notes = db.get_notes(&ids)?;
+				let untagged = notes.iter().filter(|note| !note.tags.contains(&tag));
+				print_preview(
+					&format!("add '{tag}' to"),
+					untagged.map(|note| (note.id.unwrap_or_default(), note.title.as_str())),
+				);
+				return Ok(());
+			}
+			let count = db.add_tag_to_notes(&ids, &tag)?;
+			println!("Tagged {count} of {} note(s) with '{tag}'.", ids.len());
+		}
+		TagCommand::Remove { tag, note, dry_run } => {
+			let ids = resolve_notes(db, &note)?;
+			let tag = normalize_tag(&tag);
+			if dry_run {
+				let notes = db.get_notes(&ids)?;
+				let tagged = notes.iter().filter(|note| note.tags.contains(&tag));
+				print_preview(
+					&format!("remove '{tag}' from"),
+					tagged.map(|note| (note.id.unwrap_or_default(), note.title.as_str())),
+				);
+				return Ok(());
+			}
+			let count = db.remove_tag_from_notes(&ids, &tag)?;
+			println!("Removed '{tag}' from {count} of {} note(s).", ids.len());
+		}
+	}
+	Ok(())
+}
+
+/// Prints the notes carrying any of `tags` (or a tag nested below one) as the
+/// notes `action` would change. Archived and trashed notes are listed too,
+/// since tag edits reach them as well.
+fn preview_tagged(db: &Database, action: &str, tags: &[&str]) -> Result<()> {
+	let notes = db.get_notes(&db.tagged_notes(tags)?)?;
+	let titles: Vec<String> = notes
+		.iter()
+		.map(|note| if note.deleted_at.is_some() { format!("{} (in trash)", note.title) } else { note.title.clone() })
+		.collect();
+	print_preview(action, notes.iter().zip(&titles).map(|(note, title)| (note.id.unwrap_or_default(), title.as_str())));
+	Ok(())
+}
+
+/// Prints the notes a dry run would change, after "Would {action}".
+fn print_preview<'a>(action: &str, notes: impl Iterator<Item = (i64, &'a str)>) {
+	let notes: Vec<_> = notes.collect();
+	if notes.is_empty() {
+		println!("Would {action} no notes.");
+		return;
+	}
+	println!("Would {action} {} note(s):", notes.len());
+	for (id, title) in notes {
+		println!("  [{id}] {title}");
+	}
+	println!("(dry run, nothing changed)");
+}
+
+/// Formats tags as a quoted, comma-separated list.
+fn quoted_list(tags: &[String]) -> String { tags.iter().map(|tag| format!("'{tag}'")).collect::<Vec<_>>().join(", ") }
+
+/// Resolves every note argument up front, so nothing changes if one is wrong.
+pub(super) fn resolve_notes(db: &Database, notes: &[String]) -> Result<Vec<i64>> {
+	let mut ids = notes.iter().map(|n| resolve_note(db, n.trim())).collect::<Result<Vec<_>>>()?;
+	ids.sort_unstable();
+	ids.dedup();
+	Ok(ids)
+}
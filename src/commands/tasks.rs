@@ -0,0 +1,58 @@
+use anyhow::Result;
+use chrono::Local;
+use qnote::{db::{Database, NoteQuery}, utils::parse_tags};
+
+use super::interaction::resolve_note;
+use crate::cli::TaskCommand;
+
+/// Handles the tasks command - lists tasks grouped by note
+pub fn handle_tasks(db: &Database, open: bool, tag: Option<String>, archived: bool) -> Result<()> {
+	let query = NoteQuery::new().tags_any(parse_tags(tag)).include_archived(archived);
+	let tasks = db.tasks(&query, open)?;
+	if tasks.is_empty() {
+		println!("No tasks found.");
+		return Ok(());
+	}
+
+	let today = Local::now().date_naive();
+	let mut current_note = None;
+	for task in &tasks {
+		if current_note != Some(task.note_id) {
+			current_note = Some(task.note_id);
+			println!("\n[{}] {}", task.note_id, task.note_title);
+		}
+		let checkbox = if task.done { "[x]" } else { "[ ]" };
+		let due = match task.due {
+			Some(due) if !task.done && due < today => format!("  (due {}, overdue)", due.format("%Y-%m-%d")),
+			Some(due) => format!("  (due {})", due.format("%Y-%m-%d")),
+			None => String::new(),
+		};
+		println!("  {}:{}\t{checkbox} {}{due}", task.note_id, task.line, task.text);
+	}
+
+	let open_count = tasks.iter().filter(|task| !task.done).count();
+	println!("\n{} task(s), {open_count} open", tasks.len());
+	Ok(())
+}
+
+/// Handles the task command - checks or unchecks a task in its note
+pub fn handle_task(db: &Database, command: TaskCommand) -> Result<()> {
+	let (task, done) = match command {
+		TaskCommand::Done { task } => (task, true),
+		TaskCommand::Reopen { task } => (task, false),
+	};
+	let (note, line) = task
+		.rsplit_once(':')
+		.and_then(|(note, line)| Some((note, line.trim().parse::<usize>().ok()?)))
+		.ok_or_else(|| anyhow::anyhow!("Invalid task '{task}' (expected NOTE:LINE, e.g. 42:3)"))?;
+	let id = resolve_note(db, note)?;
+
+	let changed = db.set_task_done(id, line, done)?;
+	let state = if done { "done" } else { "open" };
+	if changed {
+		println!("Marked task {id}:{line} as {state}.");
+	} else {
+		println!("Task {id}:{line} is already {state}.");
+	}
+	Ok(())
+}
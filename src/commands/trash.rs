@@ -0,0 +1,41 @@
+use anyhow::Result;
+use qnote::{db::Database, utils::format_date_full};
+
+use super::interaction::confirm;
+use crate::cli::TrashCommand;
+
+/// Handles the trash command - lists, restores or purges trashed notes
+pub fn handle_trash(db: &Database, command: TrashCommand) -> Result<()> {
+	match command {
+		TrashCommand::List => {
+			let notes = db.list_trash()?;
+			if notes.is_empty() {
+				println!("Trash is empty.");
+			}
+			for note in notes {
+				if let Some(deleted_at) = note.deleted_at {
+					println!("{}\t{}\t(deleted {})", note.id, note.title, format_date_full(&deleted_at));
+				}
+			}
+		}
+		TrashCommand::Restore { id } => {
+			if db.restore_note(id)? {
+				println!("Note {id} restored.");
+			} else {
+				anyhow::bail!("Note {id} is not in the trash");
+			}
+		}
+		TrashCommand::Empty { yes } => {
+			let count = db.list_trash()?.len();
+			if count == 0 {
+				println!("Trash is empty.");
+			} else if yes || confirm(&format!("Permanently delete {count} note(s)?")) {
+				let purged = db.empty_trash()?;
+				println!("Permanently deleted {purged} note(s).");
+			} else {
+				println!("Cancelled.");
+			}
+		}
+	}
+	Ok(())
+}
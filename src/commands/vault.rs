@@ -0,0 +1,59 @@
+use std::path::Path;
+
+use anyhow::Result;
+use qnote::{config::{data_dir, default_db_path, ensure_parent_dir, expand_home, validate_vault_name}, db::Database};
+
+use crate::{cli::VaultCommand, settings::{Config, VaultConfig}};
+
+/// Handles vault subcommands. `active` is the database this invocation
+/// resolved to, marked with `*` in the listing.
+pub fn handle_vault(mut config: Config, active: &Path, command: VaultCommand) -> Result<()> {
+	match command {
+		VaultCommand::List => {
+			let default_path = match &config.database.path {
+				Some(path) => expand_home(path),
+				None => default_db_path()?,
+			};
+			let mut vaults = vec![("default".to_string(), default_path)];
+			for name in config.vaults.keys() {
+				vaults.push((name.clone(), config.vault_path(name)?));
+			}
+			if !vaults.iter().any(|(_, path)| path == active) {
+				vaults.push(("(custom)".to_string(), active.to_path_buf()));
+			}
+
+			for (name, path) in vaults {
+				let marker = if path == active { '*' } else { ' ' };
+				println!("{marker} {name:<12} {}", path.display());
+			}
+		}
+		VaultCommand::Create { name, path } => {
+			validate_vault_name(&name)?;
+			if config.vaults.contains_key(&name) {
+				anyhow::bail!("Vault '{name}' already exists");
+			}
+			let path = match path {
+				Some(path) => path,
+				None => data_dir()?.join(format!("{name}.db")).to_string_lossy().into_owned(),
+			};
+			config.vaults.insert(name.clone(), VaultConfig { path });
+			let db_path = config.vault_path(&name)?;
+			ensure_parent_dir(&db_path)?;
+			let db_path_str = db_path.to_str().ok_or_else(|| anyhow::anyhow!("Invalid database path"))?;
+			Database::new(db_path_str, &config.database)?;
+			config.save()?;
+			println!("Created vault '{name}' at {}", db_path.display());
+		}
+		VaultCommand::Switch { name } => {
+			if name == "default" {
+				config.default_vault = None;
+			} else {
+				config.vault_path(&name)?;
+				config.default_vault = Some(name.clone());
+			}
+			config.save()?;
+			println!("Switched to vault '{name}'.");
+		}
+	}
+	Ok(())
+}
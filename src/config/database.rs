@@ -1,13 +1,15 @@
 use serde::{Deserialize, Serialize};
 
-use super::defaults::default_true;
-
 /// Database configuration.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct DatabaseConfig {
+	/// Database file (defaults to notes.db in the platform data directory)
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub path: Option<String>,
+
 	/// Enable Write-Ahead Logging for better performance (disable for network
 	/// drives)
-	#[serde(default = "default_true")]
+	#[serde(default = "default_wal_mode")]
 	pub wal_mode: bool,
 
 	/// Database cache size in kilobytes (negative value = KB, positive = pages)
@@ -21,8 +23,18 @@ pub struct DatabaseConfig {
 	/// Temp store: DEFAULT, FILE, or MEMORY
 	#[serde(default = "default_temp_store")]
 	pub temp_store: String,
+
+	/// Previous versions kept per note (0 = keep all)
+	#[serde(default = "default_max_revisions")]
+	pub max_revisions: usize,
+
+	/// Days after which trashed notes are deleted permanently (0 = never)
+	#[serde(default)]
+	pub trash_retention_days: u32,
 }
 
+const fn default_wal_mode() -> bool { true }
+
 const fn default_cache_size_kb() -> i32 {
 	-64000 // 64MB cache (negative = KB)
 }
@@ -31,13 +43,18 @@ fn default_synchronous() -> String { "NORMAL".to_string() }
 
 fn default_temp_store() -> String { "MEMORY".to_string() }
 
+const fn default_max_revisions() -> usize { 50 }
+
 impl Default for DatabaseConfig {
 	fn default() -> Self {
 		Self {
-			wal_mode:      default_true(),
-			cache_size_kb: default_cache_size_kb(),
-			synchronous:   default_synchronous(),
-			temp_store:    default_temp_store(),
+			path:                 None,
+			wal_mode:             default_wal_mode(),
+			cache_size_kb:        default_cache_size_kb(),
+			synchronous:          default_synchronous(),
+			temp_store:           default_temp_store(),
+			max_revisions:        default_max_revisions(),
+			trash_retention_days: 0,
 		}
 	}
 }
@@ -0,0 +1,41 @@
+//! Where databases live: the data directory and vault path helpers.
+
+use std::path::PathBuf;
+
+use anyhow::{Context, Result};
+
+/// Environment variable that overrides the configured database path.
+pub const DB_ENV_VAR: &str = "QNOTE_DB";
+
+/// Returns the platform-specific qnote data directory.
+pub fn data_dir() -> Result<PathBuf> { Ok(dirs::data_local_dir().unwrap_or_else(|| PathBuf::from(".")).join("qnote")) }
+
+/// Returns the database used when nothing else is configured.
+pub fn default_db_path() -> Result<PathBuf> { Ok(data_dir()?.join("notes.db")) }
+
+/// Expands a leading `~/` to the user's home directory.
+pub fn expand_home(path: &str) -> PathBuf {
+	match path.strip_prefix("~/").zip(dirs::home_dir()) {
+		Some((rest, home)) => home.join(rest),
+		None => PathBuf::from(path),
+	}
+}
+
+/// Checks that a vault name can be used as a bare TOML key in the config.
+pub fn validate_vault_name(name: &str) -> Result<()> {
+	if name.is_empty() || !name.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_') {
+		anyhow::bail!("Vault names may only contain ASCII letters, digits, '-' and '_'");
+	}
+	if name == "default" {
+		anyhow::bail!("'default' refers to the standard database and cannot be used as a vault name");
+	}
+	Ok(())
+}
+
+/// Creates the directory holding a database file if it does not exist yet.
+pub fn ensure_parent_dir(path: &std::path::Path) -> Result<()> {
+	if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+		std::fs::create_dir_all(parent).context("Failed to create database directory")?;
+	}
+	Ok(())
+}
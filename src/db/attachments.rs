@@ -0,0 +1,100 @@
+//! Files attached to notes, stored as BLOBs next to the note rows.
+
+use std::fmt::Write;
+
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use rusqlite::params;
+use sha2::{Digest, Sha256};
+
+use super::{Database, read_timestamp};
+
+/// Attachment metadata; the file contents are loaded separately.
+#[derive(Debug, Clone)]
+pub struct Attachment {
+	pub id:         i64,
+	pub note_id:    i64,
+	pub filename:   String,
+	pub mime_type:  String,
+	pub size:       u64,
+	/// Hex-encoded SHA-256 of the contents.
+	pub sha256:     String,
+	pub created_at: DateTime<Utc>,
+}
+
+const ATTACHMENT_COLUMNS: &str = "id, note_id, filename, mime_type, size, sha256, created_at";
+
+impl Attachment {
+	fn from_row(row: &rusqlite::Row) -> rusqlite::Result<Self> {
+		Ok(Self {
+			id:         row.get(0)?,
+			note_id:    row.get(1)?,
+			filename:   row.get(2)?,
+			mime_type:  row.get(3)?,
+			size:       row.get(4)?,
+			sha256:     row.get(5)?,
+			created_at: read_timestamp(row, 6)?,
+		})
+	}
+}
+
+impl Database {
+	/// Attaches a file to a note and returns the attachment ID. Attaching the
+	/// same contents under the same name again returns the existing ID.
+	pub fn add_attachment(&self, note_id: i64, filename: &str, data: &[u8]) -> Result<i64> {
+		let sha256 = Sha256::digest(data).iter().fold(String::with_capacity(64), |mut hex, byte| {
+			let _ = write!(hex, "{byte:02x}");
+			hex
+		});
+
+		let existing = self.conn.query_row(
+			"SELECT id FROM attachments WHERE note_id = ?1 AND filename = ?2 AND sha256 = ?3",
+			params![note_id, filename, &sha256],
+			|row| row.get(0),
+		);
+		match existing {
+			Ok(id) => return Ok(id),
+			Err(rusqlite::Error::QueryReturnedNoRows) => {}
+			Err(e) => return Err(e.into()),
+		}
+
+		let mime_type = mime_guess::from_path(filename).first_or_octet_stream();
+		self.conn.execute(
+			"INSERT INTO attachments (note_id, filename, mime_type, size, sha256, data, created_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+			params![note_id, filename, mime_type.essence_str(), data.len(), &sha256, data, Utc::now().timestamp_millis()],
+		)?;
+		Ok(self.conn.last_insert_rowid())
+	}
+
+	/// Returns a note's attachments in the order they were added.
+	pub fn list_attachments(&self, note_id: i64) -> Result<Vec<Attachment>> {
+		let mut stmt =
+			self.conn.prepare(&format!("SELECT {ATTACHMENT_COLUMNS} FROM attachments WHERE note_id = ?1 ORDER BY id"))?;
+
+		Ok(stmt.query_map(params![note_id], Attachment::from_row)?.collect::<Result<Vec<_>, _>>()?)
+	}
+
+	/// Retrieves an attachment's metadata by ID.
+	pub fn get_attachment(&self, id: i64) -> Result<Option<Attachment>> {
+		match self.conn.query_row(
+			&format!("SELECT {ATTACHMENT_COLUMNS} FROM attachments WHERE id = ?1"),
+			params![id],
+			Attachment::from_row,
+		) {
+			Ok(attachment) => Ok(Some(attachment)),
+			Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+			Err(e) => Err(e.into()),
+		}
+	}
+
+	/// Loads an attachment's contents.
+	pub fn attachment_data(&self, id: i64) -> Result<Vec<u8>> {
+		Ok(self.conn.query_row("SELECT data FROM attachments WHERE id = ?1", params![id], |row| row.get(0))?)
+	}
+
+	/// Deletes an attachment. Returns false if it did not exist.
+	pub fn remove_attachment(&self, id: i64) -> Result<bool> {
+		Ok(self.conn.execute("DELETE FROM attachments WHERE id = ?1", params![id])? > 0)
+	}
+}
@@ -0,0 +1,40 @@
+//! Consistent copies of a live database.
+
+use std::{fs, path::Path, time::Duration};
+
+use anyhow::{Context, Result};
+use rusqlite::{Connection, backup::Backup};
+
+use super::Database;
+
+impl Database {
+	/// Writes a consistent copy of the database to `dest`, including changes
+	/// still in the write-ahead log. Other connections may keep writing while
+	/// the copy is made.
+	///
+	/// An encrypted vault is copied as its encrypted file, so backups never hold
+	/// plaintext.
+	pub fn backup_to(&self, dest: &Path) -> Result<()> {
+		let tmp = dest.with_extension("partial");
+		if let Some(sealed) = &self.sealed {
+			self.flush()?;
+			fs::copy(sealed.path(), &tmp).context("Failed to copy encrypted vault")?;
+		} else {
+			let mut dst = Connection::open(&tmp).context("Failed to create backup file")?;
+			Backup::new(&self.conn, &mut dst)?.run_to_completion(100, Duration::from_millis(10), None)?;
+		}
+		fs::rename(&tmp, dest).context("Failed to write backup file")
+	}
+
+	/// Checks that this is an intact qnote database, e.g. before restoring it
+	/// from a backup.
+	pub fn verify(&self) -> Result<()> {
+		if self.schema_version()? == 0 {
+			anyhow::bail!("Not a qnote database");
+		}
+		if let Some(problem) = self.integrity_check()?.first() {
+			anyhow::bail!("Integrity check failed: {problem}");
+		}
+		Ok(())
+	}
+}
@@ -0,0 +1,274 @@
+//! At-rest encryption for vaults.
+//!
+//! An encrypted vault is a single file holding the whole SQLite database,
+//! sealed with ChaCha20-Poly1305 under a key derived from a passphrase with
+//! Argon2id. When unlocked, the database is decrypted into memory, so every
+//! query (including full-text search) runs on the plaintext there and the
+//! database itself is never written to disk decrypted. The one exception is
+//! the note being edited in the TUI's external editor, which sits in a temp
+//! file readable only by the user until the editor closes. Changes are
+//! written back, re-encrypted, by [`Database::flush`].
+//!
+//! File layout: `MAGIC`, format version, Argon2 memory/time/parallelism costs
+//! (little-endian u32), salt, nonce, then the ciphertext. Everything before
+//! the ciphertext is authenticated as associated data.
+
+use std::{cell::{Cell, OnceCell}, fs, io::Read, path::{Path, PathBuf}};
+
+use anyhow::{Context, Result};
+use argon2::{Algorithm, Argon2, Params, Version};
+use chacha20poly1305::{AeadCore, ChaCha20Poly1305, Key, KeyInit, aead::{Aead, OsRng, Payload, rand_core::RngCore}};
+use rusqlite::{Connection, MAIN_DB};
+
+use super::Database;
+
+/// First bytes of an encrypted vault (plain databases start with
+/// "SQLite format 3").
+const MAGIC: &[u8; 8] = b"QNOTEENC";
+const FORMAT_VERSION: u8 = 1;
+const SALT_LEN: usize = 16;
+const NONCE_LEN: usize = 12;
+const HEADER_LEN: usize = MAGIC.len() + 1 + 3 * 4 + SALT_LEN + NONCE_LEN;
+
+/// The unlocked key of an encrypted vault and where to write it back.
+pub(super) struct Sealed {
+	path:          PathBuf,
+	key:           Key,
+	salt:          [u8; SALT_LEN],
+	params:        Params,
+	/// Nonce of the file as last read or written, to notice other writers.
+	nonce:         Cell<[u8; NONCE_LEN]>,
+	/// `total_changes()` at the last flush.
+	flushed:       Cell<u64>,
+	/// Where this session's changes go once another process has written the
+	/// vault; see [`Database::flush`].
+	conflict_copy: OnceCell<PathBuf>,
+}
+
+/// Returns whether the file at `path` is an encrypted vault.
+pub fn is_encrypted(path: &Path) -> Result<bool> {
+	let mut magic = [0; MAGIC.len()];
+	match fs::File::open(path).and_then(|mut file| file.read_exact(&mut magic)) {
+		Ok(()) => Ok(&magic == MAGIC),
+		Err(e) if matches!(e.kind(), std::io::ErrorKind::NotFound | std::io::ErrorKind::UnexpectedEof) => Ok(false),
+		Err(e) => Err(e).context("Failed to read database file"),
+	}
+}
+
+impl Sealed {
+	/// Derives a new key from `passphrase` with a fresh salt.
+	fn create(path: &Path, passphrase: &str) -> Result<Self> {
+		let mut salt = [0; SALT_LEN];
+		OsRng.fill_bytes(&mut salt);
+		Self::derive(path, passphrase, salt, Params::default())
+	}
+
+	fn derive(path: &Path, passphrase: &str, salt: [u8; SALT_LEN], params: Params) -> Result<Self> {
+		let mut key = Key::default();
+		Argon2::new(Algorithm::Argon2id, Version::V0x13, params.clone())
+			.hash_password_into(passphrase.as_bytes(), &salt, &mut key)
+			.map_err(|e| anyhow::anyhow!("Key derivation failed: {e}"))?;
+		Ok(Self {
+			path: path.to_path_buf(),
+			key,
+			salt,
+			params,
+			nonce: Cell::new([0; NONCE_LEN]),
+			flushed: Cell::new(0),
+			conflict_copy: OnceCell::new(),
+		})
+	}
+
+	/// The encrypted vault file.
+	pub(super) fn path(&self) -> &Path { &self.path }
+
+	/// Reads and decrypts the vault at `path`, returning its key and the
+	/// database image.
+	fn unlock(path: &Path, passphrase: &str) -> Result<(Self, Vec<u8>)> {
+		let file = fs::read(path).context("Failed to read encrypted vault")?;
+		if file.len() < HEADER_LEN || &file[..MAGIC.len()] != MAGIC {
+			anyhow::bail!("{} is not an encrypted qnote vault", path.display());
+		}
+		let (header, ciphertext) = file.split_at(HEADER_LEN);
+		if header[MAGIC.len()] != FORMAT_VERSION {
+			anyhow::bail!("Encrypted vault format {} is not supported by this qnote", header[MAGIC.len()]);
+		}
+
+		let cost = |i: usize| {
+			let start = MAGIC.len() + 1 + 4 * i;
+			u32::from_le_bytes(header[start..start + 4].try_into().unwrap_or_default())
+		};
+		let params = Params::new(cost(0), cost(1), cost(2), None)
+			.map_err(|e| anyhow::anyhow!("Invalid key derivation parameters in vault: {e}"))?;
+		let salt_start = MAGIC.len() + 1 + 12;
+		let salt = header[salt_start..salt_start + SALT_LEN].try_into()?;
+		let nonce: [u8; NONCE_LEN] = header[salt_start + SALT_LEN..].try_into()?;
+
+		let sealed = Self::derive(path, passphrase, salt, params)?;
+		let image = ChaCha20Poly1305::new(&sealed.key)
+			.decrypt(&nonce.into(), Payload { msg: ciphertext, aad: header })
+			.map_err(|_| anyhow::anyhow!("Wrong passphrase, or the vault is damaged"))?;
+		sealed.nonce.set(nonce);
+		Ok((sealed, image))
+	}
+
+	/// Encrypts a database image and atomically replaces the vault file, or
+	/// the conflict copy once there is one.
+	fn write(&self, image: &[u8]) -> Result<()> {
+		let nonce = ChaCha20Poly1305::generate_nonce(&mut OsRng);
+		let mut file = Vec::with_capacity(HEADER_LEN + image.len() + 16);
+		file.extend_from_slice(MAGIC);
+		file.push(FORMAT_VERSION);
+		for cost in [self.params.m_cost(), self.params.t_cost(), self.params.p_cost()] {
+			file.extend_from_slice(&cost.to_le_bytes());
+		}
+		file.extend_from_slice(&self.salt);
+		file.extend_from_slice(&nonce);
+
+		let ciphertext = ChaCha20Poly1305::new(&self.key)
+			.encrypt(&nonce, Payload { msg: image, aad: &file })
+			.map_err(|_| anyhow::anyhow!("Encryption failed"))?;
+		file.extend_from_slice(&ciphertext);
+
+		match self.conflict_copy.get() {
+			Some(copy) => replace_file(copy, &file)?,
+			None => {
+				replace_file(&self.path, &file)?;
+				self.nonce.set(nonce.into());
+			}
+		}
+		Ok(())
+	}
+
+	/// Whether another process wrote the vault since it was last read or
+	/// written here.
+	fn changed_elsewhere(&self) -> Result<bool> {
+		let mut header = [0; HEADER_LEN];
+		fs::File::open(&self.path).and_then(|mut file| file.read_exact(&mut header)).context("Failed to read vault")?;
+		Ok(header[HEADER_LEN - NONCE_LEN..] != self.nonce.get())
+	}
+
+	/// Fails if another process wrote the vault since it was last read or
+	/// written here.
+	fn check_unchanged(&self) -> Result<()> {
+		if self.changed_elsewhere()? {
+			anyhow::bail!("The vault was changed by another qnote process; these changes were not saved");
+		}
+		Ok(())
+	}
+}
+
+/// Writes `contents` to a temporary file next to `path` and renames it over
+/// `path`, so readers never see a half-written file.
+fn replace_file(path: &Path, contents: &[u8]) -> Result<()> {
+	let tmp = path.with_extension("tmp");
+	#[cfg(unix)]
+	{
+		use std::{io::Write, os::unix::fs::OpenOptionsExt};
+		fs::OpenOptions::new()
+			.write(true)
+			.create(true)
+			.truncate(true)
+			.mode(0o600)
+			.open(&tmp)
+			.and_then(|mut file| file.write_all(contents).and_then(|()| file.sync_all()))
+			.context("Failed to write vault")?;
+	}
+	#[cfg(not(unix))]
+	fs::write(&tmp, contents).context("Failed to write vault")?;
+
+	fs::rename(&tmp, path).context("Failed to replace vault")
+}
+
+impl Database {
+	/// Decrypts an encrypted vault into memory without migrating it.
+	pub fn open_encrypted(path: &str, passphrase: &str, config: &crate::config::DatabaseConfig) -> Result<Self> {
+		let (sealed, image) = Sealed::unlock(Path::new(path), passphrase)?;
+		let mut conn = Connection::open_in_memory()?;
+		conn.deserialize_read_exact(MAIN_DB, image.as_slice(), image.len(), false)?;
+
+		let db = Self::configure(conn, config, Some(sealed))?;
+		db.check_schema_version()?;
+		Ok(db)
+	}
+
+	/// Decrypts an encrypted vault into memory and applies pending migrations.
+	pub fn new_encrypted(path: &str, passphrase: &str, config: &crate::config::DatabaseConfig) -> Result<Self> {
+		let db = Self::open_encrypted(path, passphrase, config)?;
+		db.prepare(config)?;
+		Ok(db)
+	}
+
+	/// Whether this database is an unlocked encrypted vault.
+	pub fn is_encrypted(&self) -> bool { self.sealed.is_some() }
+
+	/// Writes changes made to an encrypted vault back to disk. Does nothing
+	/// for plain databases, which SQLite writes as it goes.
+	///
+	/// If another process has written the vault since it was opened, the
+	/// vault is left alone: the whole database is written to a conflict copy
+	/// next to it instead, encrypted with the same passphrase, and an error
+	/// names that file. Later flushes keep updating the copy; see
+	/// [`Database::conflict_copy`].
+	pub fn flush(&self) -> Result<()> {
+		let Some(sealed) = &self.sealed else { return Ok(()) };
+		let changes = self.conn.total_changes();
+		if changes == sealed.flushed.get() {
+			return Ok(());
+		}
+
+		let image = self.conn.serialize(MAIN_DB)?;
+		let conflict = sealed.conflict_copy.get().is_none() && sealed.changed_elsewhere()?;
+		if conflict {
+			let copy =
+				PathBuf::from(format!("{}.conflict-{}", sealed.path.display(), chrono::Local::now().format("%Y%m%d-%H%M%S")));
+			let _ = sealed.conflict_copy.set(copy);
+		}
+		sealed.write(&image)?;
+		sealed.flushed.set(changes);
+
+		match sealed.conflict_copy.get() {
+			Some(copy) if conflict => anyhow::bail!(
+				"The vault was changed by another qnote process, so this session's changes were saved to {} instead",
+				copy.display()
+			),
+			_ => Ok(()),
+		}
+	}
+
+	/// The file this session's changes are being saved to instead of the vault,
+	/// if another process wrote the vault meanwhile.
+	pub fn conflict_copy(&self) -> Option<&Path> {
+		self.sealed.as_ref().and_then(|sealed| sealed.conflict_copy.get()).map(PathBuf::as_path)
+	}
+
+	/// Encrypts the database at `path` with `passphrase`, replacing the plain
+	/// file. If the vault is already encrypted, its passphrase is changed.
+	pub fn encrypt(mut self, path: &str, passphrase: &str) -> Result<()> {
+		let sealed = Sealed::create(Path::new(path), passphrase)?;
+		if let Some(current) = self.sealed.take() {
+			current.check_unchanged()?;
+		} else {
+			// Fold the write-ahead log into the main file so the image is complete
+			self.conn.pragma_update(None, "journal_mode", "DELETE")?;
+		}
+
+		sealed.write(&self.conn.serialize(MAIN_DB)?)?;
+		drop(self);
+		for suffix in ["-wal", "-shm"] {
+			let _ = fs::remove_file(format!("{path}{suffix}"));
+		}
+		Ok(())
+	}
+
+	/// Writes an unlocked vault back to `path` as a plain SQLite database.
+	pub fn decrypt(mut self, path: &str) -> Result<()> {
+		let Some(sealed) = self.sealed.take() else { anyhow::bail!("The vault is not encrypted") };
+		sealed.check_unchanged()?;
+
+		let tmp = format!("{path}.plain");
+		let _ = fs::remove_file(&tmp);
+		self.conn.execute("VACUUM INTO ?1", [&tmp])?;
+		fs::rename(&tmp, path).context("Failed to replace vault")
+	}
+}
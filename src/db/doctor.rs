@@ -0,0 +1,166 @@
+//! Consistency checks and repairs for `qnote doctor`.
+
+use anyhow::Result;
+use chrono::DateTime;
+use rusqlite::{OptionalExtension, params};
+
+use super::Database;
+
+/// Expression for a note's tags as the FTS index stores them, the same as the
+/// triggers that maintain it.
+const FTS_TAGS: &str = "(SELECT ifnull(group_concat(t.name, ' '), '') FROM note_tags nt JOIN tags t ON t.id = nt.tag_id
+                         WHERE nt.note_id = n.id)";
+
+/// Timestamp columns, stored as milliseconds since the Unix epoch, as (table,
+/// column).
+const TIMESTAMP_COLUMNS: &[(&str, &str)] = &[
+	("notes", "created_at"),
+	("notes", "updated_at"),
+	("notes", "deleted_at"),
+	("note_revisions", "saved_at"),
+	("attachments", "created_at"),
+];
+
+/// A timestamp that cannot be read back.
+#[derive(Debug, Clone)]
+pub struct BadTimestamp {
+	pub table:    &'static str,
+	pub id:       i64,
+	pub column:   &'static str,
+	pub value:    String,
+	/// The text the value was converted from when timestamps became integers,
+	/// if it could not be read then.
+	pub original: Option<String>,
+}
+
+/// Everything `qnote doctor` found wrong with a database.
+#[derive(Debug, Clone, Default)]
+pub struct HealthReport {
+	/// Messages from SQLite's integrity check (empty if it passed).
+	pub integrity:      Vec<String>,
+	/// Live notes missing from the search index.
+	pub fts_missing:    Vec<i64>,
+	/// Index entries whose note is gone or trashed.
+	pub fts_orphaned:   Vec<i64>,
+	/// Notes whose indexed title, content or tags differ from the note.
+	pub fts_stale:      Vec<i64>,
+	pub bad_timestamps: Vec<BadTimestamp>,
+	/// Revisions whose tags are not a JSON array of strings.
+	pub bad_tag_json:   Vec<i64>,
+	/// Notes whose tags were not a JSON array of strings when tags moved to
+	/// their own table, with the original value.
+	pub bad_note_tags:  Vec<(i64, String)>,
+}
+
+impl HealthReport {
+	/// Whether the search index needs rebuilding.
+	pub fn fts_out_of_sync(&self) -> bool {
+		!(self.fts_missing.is_empty() && self.fts_orphaned.is_empty() && self.fts_stale.is_empty())
+	}
+
+	/// Number of problems found.
+	pub fn problem_count(&self) -> usize {
+		self.integrity.len()
+			+ self.fts_missing.len()
+			+ self.fts_orphaned.len()
+			+ self.fts_stale.len()
+			+ self.bad_timestamps.len()
+			+ self.bad_tag_json.len()
+			+ self.bad_note_tags.len()
+	}
+}
+
+impl Database {
+	/// Runs SQLite's integrity check, including the search index's own
+	/// structure. Returns the problems found (empty if the database is sound).
+	pub fn integrity_check(&self) -> Result<Vec<String>> {
+		let mut stmt = self.conn.prepare("PRAGMA integrity_check")?;
+		let mut messages = stmt.query_map([], |row| row.get::<_, String>(0))?.collect::<rusqlite::Result<Vec<_>>>()?;
+		messages.retain(|message| message != "ok");
+
+		if let Err(e) = self.conn.execute("INSERT INTO notes_fts(notes_fts) VALUES ('integrity-check')", []) {
+			messages.push(format!("Search index: {e}"));
+		}
+		Ok(messages)
+	}
+
+	/// Checks the database for corruption, a search index out of step with the
+	/// notes, and values that fail to load.
+	pub fn check_health(&self) -> Result<HealthReport> {
+		let ids = |sql: &str| -> Result<Vec<i64>> {
+			let mut stmt = self.conn.prepare(sql)?;
+			Ok(stmt.query_map([], |row| row.get(0))?.collect::<rusqlite::Result<Vec<_>>>()?)
+		};
+
+		let mut original = self
+			.conn
+			.prepare("SELECT value FROM unreadable_timestamps WHERE table_name = ?1 AND row_id = ?2 AND column_name = ?3")?;
+		let mut bad_timestamps = Vec::new();
+		for &(table, column) in TIMESTAMP_COLUMNS {
+			let mut stmt = self.conn.prepare(&format!("SELECT id, {column} FROM {table} WHERE {column} IS NOT NULL"))?;
+			let rows = stmt.query_map([], |row| Ok((row.get::<_, i64>(0)?, row.get::<_, rusqlite::types::Value>(1)?)))?;
+			for row in rows {
+				let (id, value) = row?;
+				let value = match value {
+					rusqlite::types::Value::Integer(millis)
+						if millis > 0 && DateTime::from_timestamp_millis(millis).is_some() =>
+					{
+						continue;
+					}
+					rusqlite::types::Value::Integer(millis) => millis.to_string(),
+					rusqlite::types::Value::Text(text) => text,
+					other => format!("{other:?}"),
+				};
+				let original = original.query_row(params![table, id, column], |row| row.get(0)).optional()?;
+				bad_timestamps.push(BadTimestamp { table, id, column, value, original });
+			}
+		}
+
+		Ok(HealthReport {
+			integrity: self.integrity_check()?,
+			fts_missing: ids("SELECT id FROM notes WHERE deleted_at IS NULL AND id NOT IN (SELECT rowid FROM notes_fts)")?,
+			fts_orphaned: ids(
+				"SELECT rowid FROM notes_fts WHERE rowid NOT IN (SELECT id FROM notes WHERE deleted_at IS NULL)",
+			)?,
+			fts_stale: ids(&format!(
+				"SELECT n.id FROM notes n JOIN notes_fts f ON f.rowid = n.id
+                 WHERE n.deleted_at IS NULL
+                   AND (f.title IS NOT n.title OR f.content IS NOT n.content OR f.tags IS NOT {FTS_TAGS})"
+			))?,
+			bad_timestamps,
+			bad_tag_json: ids(
+				"SELECT id FROM note_revisions
+                 WHERE NOT json_valid(tags) OR json_type(tags) <> 'array'
+                    OR EXISTS (SELECT 1 FROM json_each(note_revisions.tags) WHERE type <> 'text')",
+			)?,
+			bad_note_tags: self
+				.conn
+				.prepare("SELECT note_id, value FROM unreadable_tags ORDER BY note_id")?
+				.query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?
+				.collect::<rusqlite::Result<Vec<_>>>()?,
+		})
+	}
+
+	/// Rebuilds the search index from the live notes. Returns the number of
+	/// notes indexed.
+	pub fn rebuild_search_index(&self) -> Result<usize> {
+		self.transaction(|db| {
+			db.conn.execute("DELETE FROM notes_fts", [])?;
+			let indexed = db.conn.execute(
+				&format!(
+					"INSERT INTO notes_fts(rowid, title, content, tags)
+                     SELECT n.id, n.title, n.content, {FTS_TAGS} FROM notes n WHERE n.deleted_at IS NULL"
+				),
+				[],
+			)?;
+			Ok(indexed)
+		})
+	}
+
+	/// Compacts the database file and refreshes the query planner statistics.
+	pub fn vacuum(&self) -> Result<()> {
+		self.conn.execute("INSERT INTO notes_fts(notes_fts) VALUES ('optimize')", [])?;
+		self.conn.execute_batch("VACUUM; PRAGMA optimize;")?;
+		Ok(())
+	}
+}
@@ -0,0 +1,69 @@
+//! Wikilinks between notes, stored in `note_links` by target title.
+
+use anyhow::Result;
+use rusqlite::params;
+
+use super::{Database, NoteSummary, SUMMARY_COLUMNS};
+use crate::utils::extract_wikilinks;
+
+/// An outgoing `[[Title]]` link and the note it currently points to.
+#[derive(Debug, Clone)]
+pub struct NoteLink {
+	pub target_title: String,
+	/// The linked note, or None if no note has that title.
+	pub target_id:    Option<i64>,
+}
+
+/// Picks the note a link title points to: live notes only, preferring
+/// unarchived ones and then the oldest.
+const RESOLVE_TITLE: &str = "SELECT t.id FROM notes t WHERE t.title = ?1 COLLATE NOCASE AND t.deleted_at IS NULL
+     ORDER BY t.archived, t.id LIMIT 1";
+
+impl Database {
+	/// Replaces a note's outgoing links with the wikilinks found in `content`.
+	pub(super) fn set_note_links(&self, note_id: i64, content: &str) -> Result<()> {
+		self.conn.execute("DELETE FROM note_links WHERE source_id = ?1", params![note_id])?;
+
+		let mut insert =
+			self.conn.prepare_cached("INSERT INTO note_links (source_id, target_title, position) VALUES (?1, ?2, ?3)")?;
+		for (position, target) in extract_wikilinks(content).iter().enumerate() {
+			insert.execute(params![note_id, target, position])?;
+		}
+		Ok(())
+	}
+
+	/// Returns the links written in a note, in order of appearance.
+	pub fn links_from(&self, note_id: i64) -> Result<Vec<NoteLink>> {
+		let resolve = RESOLVE_TITLE.replace("?1", "l.target_title");
+		let mut stmt = self.conn.prepare(&format!(
+			"SELECT l.target_title, ({resolve}) FROM note_links l WHERE l.source_id = ?1 ORDER BY l.position"
+		))?;
+
+		let links =
+			stmt.query_map(params![note_id], |row| Ok(NoteLink { target_title: row.get(0)?, target_id: row.get(1)? }))?;
+		Ok(links.collect::<Result<Vec<_>, _>>()?)
+	}
+
+	/// Returns notes outside the trash that link to this note's title, sorted
+	/// by title.
+	pub fn backlinks(&self, note_id: i64) -> Result<Vec<NoteSummary>> {
+		let mut stmt = self.conn.prepare(&format!(
+			"SELECT {SUMMARY_COLUMNS} FROM notes n
+             JOIN note_links l ON l.source_id = n.id
+             WHERE l.target_title = (SELECT title FROM notes WHERE id = ?1)
+               AND n.deleted_at IS NULL AND n.id <> ?1
+             ORDER BY n.title COLLATE NOCASE"
+		))?;
+
+		Ok(stmt.query_map(params![note_id], Self::row_to_summary)?.collect::<Result<Vec<_>, _>>()?)
+	}
+
+	/// Finds the note a `[[title]]` link points to.
+	pub fn resolve_link(&self, title: &str) -> Result<Option<i64>> {
+		match self.conn.query_row(RESOLVE_TITLE, params![title], |row| row.get(0)) {
+			Ok(id) => Ok(Some(id)),
+			Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+			Err(e) => Err(e.into()),
+		}
+	}
+}
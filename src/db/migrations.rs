@@ -0,0 +1,613 @@
+//! Versioned schema migrations keyed on `PRAGMA user_version`.
+//!
+//! Each migration runs in its own transaction together with the version bump,
+//! so a failed step leaves the database at the previous version. New steps are
+//! appended to [`MIGRATIONS`]; existing steps must never be edited once
+//! released.
+
+use anyhow::Result;
+use chrono::NaiveDate;
+use pulldown_cmark::{Event, Parser, Tag, TagEnd};
+use rusqlite::{Transaction, params};
+use uuid::Uuid;
+
+use super::Database;
+
+/// A single schema change, applied when the database is below `version`.
+struct Migration {
+	version:     i64,
+	description: &'static str,
+	up:          fn(&Transaction) -> rusqlite::Result<()>,
+}
+
+/// All migrations in the order they are applied.
+const MIGRATIONS: &[Migration] = &[
+	Migration { version: 1, description: "Create notes table with full-text index", up: create_notes },
+	Migration { version: 2, description: "Move tags into tags and note_tags tables", up: normalize_tags },
+	Migration { version: 3, description: "Add note revision history", up: create_revisions },
+	Migration { version: 4, description: "Add trash with soft delete", up: add_trash },
+	Migration { version: 5, description: "Add pinned and archived flags", up: add_pinned_archived },
+	Migration { version: 6, description: "Index wikilinks between notes", up: create_note_links },
+	Migration { version: 7, description: "Add file attachments", up: create_attachments },
+	Migration { version: 8, description: "Give every note a UUID", up: add_note_uuids },
+	Migration {
+		version:     9,
+		description: "Store timestamps as integer milliseconds",
+		up:          integer_timestamps,
+	},
+	Migration { version: 10, description: "Add note properties", up: create_note_properties },
+	Migration { version: 11, description: "Index task list items", up: create_note_tasks },
+	Migration { version: 12, description: "Add nested notebooks", up: create_notebooks },
+	Migration { version: 13, description: "Stop indexing wikilinks inside code", up: reindex_note_links },
+	Migration { version: 14, description: "Keep properties in note revisions", up: add_revision_properties },
+	Migration { version: 15, description: "Store each note's size and first line", up: add_note_summaries },
+];
+
+/// Schema version this build of qnote reads and writes.
+pub const LATEST_VERSION: i64 = MIGRATIONS[MIGRATIONS.len() - 1].version;
+
+impl Database {
+	/// Returns the schema version stored in the database file.
+	pub fn schema_version(&self) -> Result<i64> {
+		Ok(self.conn.query_row("SELECT user_version FROM pragma_user_version", [], |row| row.get(0))?)
+	}
+
+	/// Fails if the database was created by a newer qnote than this one.
+	pub(super) fn check_schema_version(&self) -> Result<()> {
+		let version = self.schema_version()?;
+		if version > LATEST_VERSION {
+			anyhow::bail!(
+				"Database schema version {version} is newer than this qnote supports ({LATEST_VERSION}). Please upgrade \
+				 qnote."
+			);
+		}
+		Ok(())
+	}
+
+	/// Returns the version and description of each migration that has not
+	/// been applied yet.
+	pub fn pending_migrations(&self) -> Result<Vec<(i64, &'static str)>> {
+		Ok(self.pending()?.iter().map(|m| (m.version, m.description)).collect())
+	}
+
+	/// Applies all pending migrations in order and returns the version and
+	/// description of the ones applied.
+	pub fn migrate(&self) -> Result<Vec<(i64, &'static str)>> {
+		let pending = self.pending()?;
+		for migration in &pending {
+			let tx = self.conn.unchecked_transaction()?;
+			(migration.up)(&tx)
+				.map_err(|e| anyhow::anyhow!("Migration {} ({}) failed: {e}", migration.version, migration.description))?;
+			tx.pragma_update(None, "user_version", migration.version)?;
+			tx.commit()?;
+		}
+		Ok(pending.iter().map(|m| (m.version, m.description)).collect())
+	}
+
+	/// Returns the migrations above the stored schema version.
+	fn pending(&self) -> Result<Vec<&'static Migration>> {
+		let version = self.schema_version()?;
+		Ok(MIGRATIONS.iter().filter(|m| m.version > version).collect())
+	}
+}
+
+/// v1: the original schema. Uses `IF NOT EXISTS` because databases created
+/// before versioning already have these tables, and rebuilds the FTS index
+/// since older triggers could leave it out of sync.
+fn create_notes(tx: &Transaction) -> rusqlite::Result<()> {
+	tx.execute_batch(
+		"CREATE TABLE IF NOT EXISTS notes (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            title TEXT NOT NULL,
+            content TEXT NOT NULL,
+            tags TEXT NOT NULL,
+            created_at TEXT NOT NULL,
+            updated_at TEXT NOT NULL
+        );
+
+        CREATE INDEX IF NOT EXISTS idx_notes_updated_at ON notes(updated_at DESC);
+        CREATE INDEX IF NOT EXISTS idx_notes_created_at ON notes(created_at DESC);
+        CREATE INDEX IF NOT EXISTS idx_notes_title ON notes(title COLLATE NOCASE);
+
+        CREATE VIRTUAL TABLE IF NOT EXISTS notes_fts USING fts5(
+            title, content, tags, content='notes', content_rowid='id'
+        );
+
+        DROP TRIGGER IF EXISTS notes_ai;
+        DROP TRIGGER IF EXISTS notes_ad;
+        DROP TRIGGER IF EXISTS notes_au;
+
+        CREATE TRIGGER notes_ai AFTER INSERT ON notes BEGIN
+            INSERT INTO notes_fts(rowid, title, content, tags)
+            VALUES (new.id, new.title, new.content, new.tags);
+        END;
+
+        CREATE TRIGGER notes_ad AFTER DELETE ON notes BEGIN
+            INSERT INTO notes_fts(notes_fts, rowid, title, content, tags)
+            VALUES ('delete', old.id, old.title, old.content, old.tags);
+        END;
+
+        CREATE TRIGGER notes_au AFTER UPDATE ON notes BEGIN
+            INSERT INTO notes_fts(notes_fts, rowid, title, content, tags)
+            VALUES ('delete', old.id, old.title, old.content, old.tags);
+            INSERT INTO notes_fts(rowid, title, content, tags)
+            VALUES (new.id, new.title, new.content, new.tags);
+        END;
+
+        INSERT INTO notes_fts(notes_fts) VALUES ('rebuild');",
+	)
+}
+
+/// v2: replaces the JSON `notes.tags` column with a `tags`/`note_tags`
+/// relation. The FTS index becomes self-contained, since its tags column no
+/// longer has a counterpart in `notes`, and is kept in sync by triggers on
+/// both tables. Tags that end up unused are removed automatically. Values
+/// that are not a JSON array of strings keep whatever text tags they hold, and
+/// the original value is kept in `unreadable_tags` for `qnote doctor`.
+fn normalize_tags(tx: &Transaction) -> rusqlite::Result<()> {
+	tx.execute_batch(
+		"CREATE TABLE unreadable_tags (
+            note_id INTEGER PRIMARY KEY,
+            value TEXT NOT NULL
+        );
+
+        INSERT INTO unreadable_tags (note_id, value)
+        SELECT id, tags FROM notes
+        WHERE CASE WHEN json_valid(tags)
+                   THEN json_type(tags) <> 'array' OR EXISTS (SELECT 1 FROM json_each(notes.tags) WHERE type <> 'text')
+                   ELSE 1 END;
+
+        CREATE TABLE tags (
+            id INTEGER PRIMARY KEY,
+            name TEXT NOT NULL UNIQUE
+        );
+
+        CREATE TABLE note_tags (
+            note_id INTEGER NOT NULL REFERENCES notes(id) ON DELETE CASCADE,
+            tag_id INTEGER NOT NULL REFERENCES tags(id) ON DELETE CASCADE,
+            PRIMARY KEY (note_id, tag_id)
+        ) WITHOUT ROWID;
+
+        CREATE INDEX idx_note_tags_tag ON note_tags(tag_id, note_id);
+
+        INSERT OR IGNORE INTO tags(name)
+        SELECT DISTINCT trim(j.value) FROM notes, json_each(notes.tags) j
+        WHERE json_valid(notes.tags) AND json_type(notes.tags) = 'array' AND j.type = 'text' AND trim(j.value) <> '';
+
+        INSERT OR IGNORE INTO note_tags(note_id, tag_id)
+        SELECT notes.id, tags.id FROM notes, json_each(notes.tags) j
+        JOIN tags ON tags.name = trim(j.value)
+        WHERE json_valid(notes.tags) AND json_type(notes.tags) = 'array' AND j.type = 'text';
+
+        DROP TRIGGER notes_ai;
+        DROP TRIGGER notes_ad;
+        DROP TRIGGER notes_au;
+        DROP TABLE notes_fts;
+        ALTER TABLE notes DROP COLUMN tags;
+
+        CREATE VIRTUAL TABLE notes_fts USING fts5(title, content, tags);
+
+        INSERT INTO notes_fts(rowid, title, content, tags)
+        SELECT id, title, content,
+               (SELECT ifnull(group_concat(t.name, ' '), '') FROM note_tags nt JOIN tags t ON t.id = nt.tag_id
+                WHERE nt.note_id = notes.id)
+        FROM notes;
+
+        CREATE TRIGGER notes_ai AFTER INSERT ON notes BEGIN
+            INSERT INTO notes_fts(rowid, title, content, tags) VALUES (new.id, new.title, new.content, '');
+        END;
+
+        CREATE TRIGGER notes_ad AFTER DELETE ON notes BEGIN
+            DELETE FROM notes_fts WHERE rowid = old.id;
+        END;
+
+        CREATE TRIGGER notes_au AFTER UPDATE OF title, content ON notes BEGIN
+            UPDATE notes_fts SET title = new.title, content = new.content WHERE rowid = new.id;
+        END;
+
+        CREATE TRIGGER note_tags_ai AFTER INSERT ON note_tags BEGIN
+            UPDATE notes_fts SET tags = (
+                SELECT ifnull(group_concat(t.name, ' '), '') FROM note_tags nt JOIN tags t ON t.id = nt.tag_id
+                WHERE nt.note_id = new.note_id
+            ) WHERE rowid = new.note_id;
+        END;
+
+        CREATE TRIGGER note_tags_ad AFTER DELETE ON note_tags BEGIN
+            UPDATE notes_fts SET tags = (
+                SELECT ifnull(group_concat(t.name, ' '), '') FROM note_tags nt JOIN tags t ON t.id = nt.tag_id
+                WHERE nt.note_id = old.note_id
+            ) WHERE rowid = old.note_id;
+            DELETE FROM tags WHERE id = old.tag_id AND NOT EXISTS (SELECT 1 FROM note_tags WHERE tag_id = old.tag_id);
+        END;
+
+        CREATE TRIGGER tags_au AFTER UPDATE OF name ON tags BEGIN
+            UPDATE notes_fts SET tags = (
+                SELECT ifnull(group_concat(t.name, ' '), '') FROM note_tags nt JOIN tags t ON t.id = nt.tag_id
+                WHERE nt.note_id = notes_fts.rowid
+            ) WHERE rowid IN (SELECT note_id FROM note_tags WHERE tag_id = new.id);
+        END;",
+	)
+}
+
+/// v3: previous versions of notes, written by `update_note`.
+fn create_revisions(tx: &Transaction) -> rusqlite::Result<()> {
+	tx.execute_batch(
+		"CREATE TABLE note_revisions (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            note_id INTEGER NOT NULL REFERENCES notes(id) ON DELETE CASCADE,
+            title TEXT NOT NULL,
+            content TEXT NOT NULL,
+            tags TEXT NOT NULL,
+            saved_at TEXT NOT NULL
+        );
+
+        CREATE INDEX idx_note_revisions_note ON note_revisions(note_id, id DESC);",
+	)
+}
+
+/// v4: soft delete. Trashed notes keep their rows but leave the FTS index
+/// until they are restored.
+fn add_trash(tx: &Transaction) -> rusqlite::Result<()> {
+	tx.execute_batch(
+		"ALTER TABLE notes ADD COLUMN deleted_at TEXT;
+
+        CREATE INDEX idx_notes_deleted_at ON notes(deleted_at);
+
+        CREATE TRIGGER notes_trash AFTER UPDATE OF deleted_at ON notes
+        WHEN old.deleted_at IS NULL AND new.deleted_at IS NOT NULL BEGIN
+            DELETE FROM notes_fts WHERE rowid = new.id;
+        END;
+
+        CREATE TRIGGER notes_untrash AFTER UPDATE OF deleted_at ON notes
+        WHEN old.deleted_at IS NOT NULL AND new.deleted_at IS NULL BEGIN
+            INSERT INTO notes_fts(rowid, title, content, tags)
+            VALUES (new.id, new.title, new.content, (
+                SELECT ifnull(group_concat(t.name, ' '), '') FROM note_tags nt JOIN tags t ON t.id = nt.tag_id
+                WHERE nt.note_id = new.id
+            ));
+        END;",
+	)
+}
+
+/// v5: pinned notes sort first; archived notes are hidden by default.
+fn add_pinned_archived(tx: &Transaction) -> rusqlite::Result<()> {
+	tx.execute_batch(
+		"ALTER TABLE notes ADD COLUMN pinned INTEGER NOT NULL DEFAULT 0;
+        ALTER TABLE notes ADD COLUMN archived INTEGER NOT NULL DEFAULT 0;",
+	)
+}
+
+/// v6: `[[Title]]` links, keyed by target title so they resolve to whichever
+/// note carries that title. Existing notes are scanned for links.
+fn create_note_links(tx: &Transaction) -> rusqlite::Result<()> {
+	tx.execute_batch(
+		"CREATE TABLE note_links (
+            source_id INTEGER NOT NULL REFERENCES notes(id) ON DELETE CASCADE,
+            target_title TEXT NOT NULL COLLATE NOCASE,
+            position INTEGER NOT NULL,
+            PRIMARY KEY (source_id, target_title)
+        ) WITHOUT ROWID;
+
+        CREATE INDEX idx_note_links_target ON note_links(target_title);",
+	)?;
+
+	let mut notes = tx.prepare("SELECT id, content FROM notes")?;
+	let mut insert = tx.prepare("INSERT INTO note_links (source_id, target_title, position) VALUES (?1, ?2, ?3)")?;
+	let rows = notes.query_map([], |row| Ok((row.get::<_, i64>(0)?, row.get::<_, String>(1)?)))?;
+	for row in rows {
+		let (id, content) = row?;
+		for (position, target) in scan_wikilinks(&content).iter().enumerate() {
+			insert.execute(params![id, target, position])?;
+		}
+	}
+	Ok(())
+}
+
+/// v7: files attached to notes, stored inline as BLOBs.
+fn create_attachments(tx: &Transaction) -> rusqlite::Result<()> {
+	tx.execute_batch(
+		"CREATE TABLE attachments (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            note_id INTEGER NOT NULL REFERENCES notes(id) ON DELETE CASCADE,
+            filename TEXT NOT NULL,
+            mime_type TEXT NOT NULL,
+            size INTEGER NOT NULL,
+            sha256 TEXT NOT NULL,
+            data BLOB NOT NULL,
+            created_at TEXT NOT NULL
+        );
+
+        CREATE INDEX idx_attachments_note ON attachments(note_id, id);",
+	)
+}
+
+/// v8: stable identifiers that survive export and import, unlike `id`.
+fn add_note_uuids(tx: &Transaction) -> rusqlite::Result<()> {
+	tx.execute_batch("ALTER TABLE notes ADD COLUMN uuid TEXT;")?;
+
+	let ids =
+		tx.prepare("SELECT id FROM notes")?.query_map([], |row| row.get::<_, i64>(0))?.collect::<Result<Vec<_>, _>>()?;
+	let mut update = tx.prepare("UPDATE notes SET uuid = ?1 WHERE id = ?2")?;
+	for id in ids {
+		update.execute(params![Uuid::new_v4().to_string(), id])?;
+	}
+
+	tx.execute_batch("CREATE UNIQUE INDEX idx_notes_uuid ON notes(uuid);")
+}
+
+/// v9: timestamps become milliseconds since the Unix epoch, which sort and
+/// compare without being parsed. Columns cannot change type in place, so each
+/// is renamed, re-added as INTEGER, filled and dropped. Values SQLite cannot
+/// read become 0 and their original text is kept in `unreadable_timestamps`,
+/// so `qnote doctor` can report what they were.
+fn integer_timestamps(tx: &Transaction) -> rusqlite::Result<()> {
+	// Columns referenced by indexes or triggers cannot be dropped
+	tx.execute_batch(
+		"DROP TRIGGER notes_trash;
+        DROP TRIGGER notes_untrash;
+        DROP INDEX idx_notes_updated_at;
+        DROP INDEX idx_notes_created_at;
+        DROP INDEX idx_notes_deleted_at;
+
+        CREATE TABLE unreadable_timestamps (
+            table_name TEXT NOT NULL,
+            row_id INTEGER NOT NULL,
+            column_name TEXT NOT NULL,
+            value TEXT NOT NULL,
+            PRIMARY KEY (table_name, row_id, column_name)
+        );",
+	)?;
+
+	let columns = [
+		("notes", "created_at", "NOT NULL DEFAULT 0"),
+		("notes", "updated_at", "NOT NULL DEFAULT 0"),
+		("notes", "deleted_at", ""),
+		("note_revisions", "saved_at", "NOT NULL DEFAULT 0"),
+		("attachments", "created_at", "NOT NULL DEFAULT 0"),
+	];
+	for (table, column, constraint) in columns {
+		tx.execute_batch(&format!(
+			"INSERT INTO unreadable_timestamps (table_name, row_id, column_name, value)
+            SELECT '{table}', id, '{column}', {column} FROM {table}
+            WHERE {column} IS NOT NULL AND julianday({column}) IS NULL;
+            ALTER TABLE {table} RENAME COLUMN {column} TO old_{column};
+            ALTER TABLE {table} ADD COLUMN {column} INTEGER {constraint};
+            UPDATE {table} SET {column} = ifnull(
+                CAST(round((julianday(old_{column}) - 2440587.5) * 86400000) AS INTEGER),
+                CASE WHEN old_{column} IS NULL THEN NULL ELSE 0 END
+            );
+            ALTER TABLE {table} DROP COLUMN old_{column};"
+		))?;
+	}
+
+	tx.execute_batch(
+		"CREATE INDEX idx_notes_updated_at ON notes(updated_at DESC);
+        CREATE INDEX idx_notes_created_at ON notes(created_at DESC);
+        CREATE INDEX idx_notes_deleted_at ON notes(deleted_at);
+
+        CREATE TRIGGER notes_trash AFTER UPDATE OF deleted_at ON notes
+        WHEN old.deleted_at IS NULL AND new.deleted_at IS NOT NULL BEGIN
+            DELETE FROM notes_fts WHERE rowid = new.id;
+        END;
+
+        CREATE TRIGGER notes_untrash AFTER UPDATE OF deleted_at ON notes
+        WHEN old.deleted_at IS NOT NULL AND new.deleted_at IS NULL BEGIN
+            INSERT INTO notes_fts(rowid, title, content, tags)
+            VALUES (new.id, new.title, new.content, (
+                SELECT ifnull(group_concat(t.name, ' '), '') FROM note_tags nt JOIN tags t ON t.id = nt.tag_id
+                WHERE nt.note_id = new.id
+            ));
+        END;",
+	)
+}
+
+/// v10: typed key/value properties. `value` holds the text form and `kind`
+/// its type, so filters can compare the text form directly.
+fn create_note_properties(tx: &Transaction) -> rusqlite::Result<()> {
+	tx.execute_batch(
+		"CREATE TABLE note_properties (
+            note_id INTEGER NOT NULL REFERENCES notes(id) ON DELETE CASCADE,
+            key TEXT NOT NULL,
+            kind TEXT NOT NULL,
+            value TEXT NOT NULL,
+            PRIMARY KEY (note_id, key)
+        ) WITHOUT ROWID;
+
+        CREATE INDEX idx_note_properties_key ON note_properties(key, value);",
+	)
+}
+
+/// v11: `- [ ]` task list items, re-indexed whenever a note is saved.
+/// Existing notes are scanned for tasks.
+fn create_note_tasks(tx: &Transaction) -> rusqlite::Result<()> {
+	tx.execute_batch(
+		"CREATE TABLE note_tasks (
+            note_id INTEGER NOT NULL REFERENCES notes(id) ON DELETE CASCADE,
+            line INTEGER NOT NULL,
+            text TEXT NOT NULL,
+            done INTEGER NOT NULL,
+            due TEXT,
+            PRIMARY KEY (note_id, line)
+        ) WITHOUT ROWID;
+
+        CREATE INDEX idx_note_tasks_due ON note_tasks(done, due);",
+	)?;
+
+	let mut notes = tx.prepare("SELECT id, content FROM notes")?;
+	let mut insert = tx.prepare("INSERT INTO note_tasks (note_id, line, text, done, due) VALUES (?1, ?2, ?3, ?4, ?5)")?;
+	let rows = notes.query_map([], |row| Ok((row.get::<_, i64>(0)?, row.get::<_, String>(1)?)))?;
+	for row in rows {
+		let (id, content) = row?;
+		for (line, text, done, due) in scan_tasks(&content) {
+			insert.execute(params![id, line, text, done, due])?;
+		}
+	}
+	Ok(())
+}
+
+/// v12: notebooks nested by `parent_id`, and the notebook each note is filed
+/// in. Deleting a notebook deletes the notebooks below it and leaves their
+/// notes outside any notebook.
+fn create_notebooks(tx: &Transaction) -> rusqlite::Result<()> {
+	tx.execute_batch(
+		"CREATE TABLE notebooks (
+            id INTEGER PRIMARY KEY,
+            name TEXT NOT NULL,
+            parent_id INTEGER REFERENCES notebooks(id) ON DELETE CASCADE
+        );
+
+        CREATE UNIQUE INDEX idx_notebooks_name ON notebooks(ifnull(parent_id, 0), name);
+        ALTER TABLE notes ADD COLUMN notebook_id INTEGER REFERENCES notebooks(id) ON DELETE SET NULL;
+        CREATE INDEX idx_notes_notebook ON notes(notebook_id);",
+	)
+}
+
+/// v13: re-indexes every note's links, leaving out `[[...]]` inside code
+/// spans and code blocks, which v6 and earlier saves took for links.
+fn reindex_note_links(tx: &Transaction) -> rusqlite::Result<()> {
+	tx.execute("DELETE FROM note_links", [])?;
+	let mut notes = tx.prepare("SELECT id, content FROM notes")?;
+	let mut insert = tx.prepare("INSERT INTO note_links (source_id, target_title, position) VALUES (?1, ?2, ?3)")?;
+	let rows = notes.query_map([], |row| Ok((row.get::<_, i64>(0)?, row.get::<_, String>(1)?)))?;
+	for row in rows {
+		let (id, content) = row?;
+		let (mut targets, mut run, mut in_code_block) = (Vec::new(), String::new(), false);
+		for event in Parser::new(&content).chain([Event::HardBreak]) {
+			match event {
+				Event::Text(text) if !in_code_block => {
+					run.push_str(&text);
+					continue;
+				}
+				Event::Start(Tag::CodeBlock(_)) => in_code_block = true,
+				Event::End(TagEnd::CodeBlock) => in_code_block = false,
+				_ => {}
+			}
+			for target in scan_wikilinks(&std::mem::take(&mut run)) {
+				if !targets.iter().any(|t: &String| t.eq_ignore_ascii_case(&target)) {
+					targets.push(target);
+				}
+			}
+		}
+		for (position, target) in targets.iter().enumerate() {
+			insert.execute(params![id, target, position])?;
+		}
+	}
+	Ok(())
+}
+
+/// v14: revisions keep the note's properties too, encoded like the
+/// properties column of `NOTE_COLUMNS`. Older revisions have none recorded
+/// (NULL), so restoring one leaves the note's properties alone.
+fn add_revision_properties(tx: &Transaction) -> rusqlite::Result<()> {
+	tx.execute_batch("ALTER TABLE note_revisions ADD COLUMN properties TEXT;")
+}
+
+/// v15: each note's content size in bytes and first non-blank line (up to 80
+/// characters), kept up to date by triggers so listings need not read the
+/// content.
+fn add_note_summaries(tx: &Transaction) -> rusqlite::Result<()> {
+	let summary = |content: &str| {
+		let start = format!("substr(ltrim({content}, char(9, 10, 13, 32)), 1, 200)");
+		format!(
+			"content_size = length(CAST({content} AS BLOB)),
+             first_line = substr(rtrim(substr({start}, 1, instr({start} || char(10), char(10)) - 1), char(9, 13, 32)), 1, 80)"
+		)
+	};
+	tx.execute_batch(&format!(
+		"ALTER TABLE notes ADD COLUMN content_size INTEGER NOT NULL DEFAULT 0;
+        ALTER TABLE notes ADD COLUMN first_line TEXT NOT NULL DEFAULT '';
+        UPDATE notes SET {all};
+
+        CREATE TRIGGER notes_summary_ai AFTER INSERT ON notes BEGIN
+            UPDATE notes SET {new} WHERE id = new.id;
+        END;
+
+        CREATE TRIGGER notes_summary_au AFTER UPDATE OF content ON notes BEGIN
+            UPDATE notes SET {new} WHERE id = new.id;
+        END;",
+		all = summary("content"),
+		new = summary("new.content"),
+	))
+}
+
+/// Distinct `[[Title]]` and `[[Title|label]]` targets in `text`, in order of
+/// appearance. A frozen copy of the scanner the link migrations were written
+/// against, so changes to `utils` cannot change what they do.
+fn scan_wikilinks(text: &str) -> Vec<String> {
+	let mut targets: Vec<String> = Vec::new();
+	let mut pos = 0;
+	while let Some(start) = text[pos..].find("[[").map(|i| pos + i) {
+		let inner_start = start + 2;
+		let Some(len) = text[inner_start..].find("]]") else {
+			break;
+		};
+		let inner = &text[inner_start..inner_start + len];
+		if let Some(nested) = inner.rfind("[[") {
+			pos = inner_start + nested;
+			continue;
+		}
+
+		let target = inner.split('|').next().unwrap_or_default().trim();
+		if !inner.contains('\n') && !target.is_empty() && !targets.iter().any(|t| t.eq_ignore_ascii_case(target)) {
+			targets.push(target.to_string());
+		}
+		pos = inner_start + len + 2;
+	}
+	targets
+}
+
+/// Task list items in `content` as (line, text, done, due date), skipping
+/// fenced code blocks. A frozen copy of the scanner the task migration was
+/// written against, so changes to `utils` cannot change what it does.
+fn scan_tasks(content: &str) -> Vec<(usize, String, bool, Option<String>)> {
+	let mut tasks = Vec::new();
+	let mut in_fence = false;
+
+	for (index, line) in content.lines().enumerate() {
+		let trimmed = line.trim_start();
+		if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+			in_fence = !in_fence;
+			continue;
+		}
+		let Some((start, done)) = scan_task_checkbox(line).filter(|_| !in_fence) else { continue };
+
+		let mut due = None;
+		let words: Vec<&str> = line[start + 3..]
+			.split_whitespace()
+			.filter(|word| {
+				let date = word.strip_prefix("due:").and_then(|d| NaiveDate::parse_from_str(d, "%Y-%m-%d").ok());
+				due = due.or(date);
+				date.is_none()
+			})
+			.collect();
+		tasks.push((index + 1, words.join(" "), done, due.map(|date| date.format("%Y-%m-%d").to_string())));
+	}
+
+	tasks
+}
+
+/// Byte offset of a task list item's `[` and whether it is checked, for
+/// [`scan_tasks`].
+fn scan_task_checkbox(line: &str) -> Option<(usize, bool)> {
+	let trimmed = line.trim_start();
+	let after_marker = match trimmed.strip_prefix(['-', '*', '+']) {
+		Some(rest) => rest,
+		None => {
+			let digits = trimmed.len() - trimmed.trim_start_matches(|c: char| c.is_ascii_digit()).len();
+			if digits == 0 {
+				return None;
+			}
+			trimmed[digits..].strip_prefix(['.', ')'])?
+		}
+	};
+	let checkbox = after_marker.strip_prefix([' ', '\t'])?.trim_start_matches([' ', '\t']);
+	let done = match checkbox.get(..3)? {
+		"[ ]" => false,
+		"[x]" | "[X]" => true,
+		_ => return None,
+	};
+	if !checkbox[3..].is_empty() && !checkbox[3..].starts_with(char::is_whitespace) {
+		return None;
+	}
+	Some((line.len() - checkbox.len(), done))
+}
@@ -0,0 +1,496 @@
+//! SQLite database layer for note CRUD operations with full-text search.
+
+mod attachments;
+mod backup;
+mod crypto;
+mod doctor;
+mod links;
+mod migrations;
+mod notebooks;
+mod properties;
+mod query;
+mod revisions;
+mod search;
+mod tags;
+mod tasks;
+mod trash;
+
+use std::{collections::BTreeMap, fmt};
+
+use anyhow::Result;
+pub use attachments::Attachment;
+use chrono::{DateTime, Utc};
+pub use crypto::is_encrypted;
+pub use doctor::{BadTimestamp, HealthReport};
+pub use links::NoteLink;
+pub use migrations::LATEST_VERSION;
+pub use notebooks::NotebookNode;
+pub use properties::{PropertyValue, validate_property_key};
+pub use query::{NoteQuery, SortKey};
+pub use revisions::Revision;
+use rusqlite::{Connection, params};
+pub use search::{QueryError, SearchResult};
+pub use tags::TagNode;
+pub use tasks::Task;
+use uuid::Uuid;
+
+use crate::config::DatabaseConfig;
+
+/// A note with title, content, tags, and timestamps.
+#[derive(Debug, Clone)]
+pub struct Note {
+	pub id:          Option<i64>,
+	pub title:       String,
+	pub content:     String,
+	pub tags:        Vec<String>,
+	pub created_at:  DateTime<Utc>,
+	pub updated_at:  DateTime<Utc>,
+	/// When the note was moved to the trash (None for live notes).
+	pub deleted_at:  Option<DateTime<Utc>>,
+	/// Pinned notes are listed before all others.
+	pub pinned:      bool,
+	/// Archived notes are hidden from lists and search unless asked for.
+	pub archived:    bool,
+	/// Stable identifier kept across export and import.
+	pub uuid:        String,
+	pub properties:  BTreeMap<String, PropertyValue>,
+	/// Notebook the note is filed in, if any.
+	pub notebook_id: Option<i64>,
+}
+
+impl Note {
+	/// Creates a new note with current timestamp (id is None until saved).
+	pub fn new(title: String, content: String, tags: Vec<String>) -> Self {
+		let now = Utc::now();
+		Self {
+			id: None,
+			title,
+			content,
+			tags,
+			created_at: now,
+			updated_at: now,
+			deleted_at: None,
+			pinned: false,
+			archived: false,
+			uuid: Uuid::new_v4().to_string(),
+			properties: BTreeMap::new(),
+			notebook_id: None,
+		}
+	}
+
+	/// Whether the note carries exactly `tags`, in any order.
+	pub fn has_tags(&self, tags: &[String]) -> bool {
+		let mut ours: Vec<&String> = self.tags.iter().collect();
+		let mut theirs: Vec<&String> = tags.iter().collect();
+		ours.sort_unstable();
+		ours.dedup();
+		theirs.sort_unstable();
+		theirs.dedup();
+		ours == theirs
+	}
+}
+
+/// A note without its content, for listings. Cheap to load for every note.
+#[derive(Debug, Clone)]
+pub struct NoteSummary {
+	pub id:         i64,
+	pub title:      String,
+	pub tags:       Vec<String>,
+	pub created_at: DateTime<Utc>,
+	pub updated_at: DateTime<Utc>,
+	pub deleted_at: Option<DateTime<Utc>>,
+	pub pinned:     bool,
+	pub archived:   bool,
+	/// Size of the content in bytes.
+	pub size:       u64,
+	/// First non-blank line of the content, cut to 80 characters.
+	pub first_line: String,
+}
+
+/// Aggregate figures for the stats command.
+#[derive(Debug, Clone)]
+pub struct NoteStats {
+	pub note_count:  usize,
+	pub tag_count:   usize,
+	pub total_bytes: usize,
+	/// Title and creation time of the oldest note.
+	pub oldest:      Option<(String, DateTime<Utc>)>,
+	/// Title and update time of the most recently updated note.
+	pub newest:      Option<(String, DateTime<Utc>)>,
+}
+
+/// An edit refused because the note was changed (e.g. by another qnote) after
+/// the editor read it.
+#[derive(Debug)]
+pub struct EditConflict {
+	pub id: i64,
+}
+
+impl fmt::Display for EditConflict {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		write!(f, "Note {} was changed elsewhere since it was read; not overwriting it", self.id)
+	}
+}
+
+impl std::error::Error for EditConflict {}
+
+/// What [`Database::import_notes`] did with one note.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ImportOutcome {
+	/// No note had its UUID, so it was added with this ID.
+	Created(i64),
+	/// The note with its UUID was overwritten.
+	Updated(i64),
+	/// The note with its UUID already had the same title, content, tags and
+	/// properties.
+	Unchanged(i64),
+}
+
+/// Columns selected for a note from `notes n`, in the order `row_to_note`
+/// reads them. Tags are aggregated into a JSON array sorted by name.
+const NOTE_COLUMNS: &str = "n.id, n.title, n.content,
+    (SELECT json_group_array(name) FROM (
+        SELECT t.name FROM note_tags nt JOIN tags t ON t.id = nt.tag_id WHERE nt.note_id = n.id ORDER BY t.name
+    )),
+    n.created_at, n.updated_at, n.deleted_at, n.pinned, n.archived, n.uuid,
+    (SELECT json_group_object(p.key, json_array(p.kind, p.value)) FROM note_properties p WHERE p.note_id = n.id),
+    n.notebook_id";
+
+/// Columns selected for a note summary from `notes n`, in the order
+/// `row_to_summary` reads them. The content itself is not read; its size and
+/// first line are stored alongside it.
+const SUMMARY_COLUMNS: &str = "n.id, n.title,
+    (SELECT json_group_array(name) FROM (
+        SELECT t.name FROM note_tags nt JOIN tags t ON t.id = nt.tag_id WHERE nt.note_id = n.id ORDER BY t.name
+    )),
+    n.created_at, n.updated_at, n.deleted_at, n.pinned, n.archived,
+    n.content_size, n.first_line";
+
+/// Number of columns in [`SUMMARY_COLUMNS`], for queries that select more.
+const SUMMARY_COLUMN_COUNT: usize = 10;
+
+/// SQLite database wrapper for note storage and retrieval.
+pub struct Database {
+	conn:          Connection,
+	/// Revisions kept per note (0 keeps all).
+	max_revisions: usize,
+	/// Key and file of an encrypted vault, whose database lives in memory.
+	sealed:        Option<crypto::Sealed>,
+}
+
+impl Database {
+	/// Opens or creates a database with WAL mode and FTS5 support, applying any
+	/// pending schema migrations.
+	pub fn new(path: &str, config: &DatabaseConfig) -> Result<Self> {
+		let db = Self::open(path, config)?;
+		db.prepare(config)?;
+		Ok(db)
+	}
+
+	/// Opens a database without migrating it. Refuses databases whose schema
+	/// was written by a newer version of qnote, and encrypted vaults (see
+	/// [`Database::open_encrypted`]).
+	pub fn open(path: &str, config: &DatabaseConfig) -> Result<Self> {
+		if is_encrypted(std::path::Path::new(path))? {
+			anyhow::bail!("{path} is an encrypted vault and needs a passphrase");
+		}
+		let db = Self::configure(Connection::open(path)?, config, None)?;
+		db.check_schema_version()?;
+		Ok(db)
+	}
+
+	/// Applies the configured connection settings.
+	fn configure(conn: Connection, config: &DatabaseConfig, sealed: Option<crypto::Sealed>) -> Result<Self> {
+		// Configure database performance settings (an in-memory vault keeps its own
+		// journal)
+		if sealed.is_none() {
+			conn.pragma_update(None, "journal_mode", if config.wal_mode { "WAL" } else { "DELETE" })?;
+		}
+		conn.pragma_update(None, "synchronous", &config.synchronous)?;
+		conn.pragma_update(None, "cache_size", config.cache_size_kb)?;
+		conn.pragma_update(None, "temp_store", &config.temp_store)?;
+		conn.pragma_update(None, "foreign_keys", true)?;
+
+		Ok(Self { conn, max_revisions: config.max_revisions, sealed })
+	}
+
+	/// Applies pending migrations and purges expired trash.
+	fn prepare(&self, config: &DatabaseConfig) -> Result<()> {
+		self.migrate()?;
+		if config.trash_retention_days > 0 {
+			self.purge_trash_older_than(config.trash_retention_days)?;
+		}
+		Ok(())
+	}
+
+	/// Converts a database row to a Note.
+	fn row_to_note(row: &rusqlite::Row) -> rusqlite::Result<Note> {
+		let tags_json: String = row.get(3)?;
+		let tags: Vec<String> = serde_json::from_str(&tags_json).unwrap_or_default();
+
+		Ok(Note {
+			id: Some(row.get(0)?),
+			title: row.get(1)?,
+			content: row.get(2)?,
+			tags,
+			created_at: read_timestamp(row, 4)?,
+			updated_at: read_timestamp(row, 5)?,
+			deleted_at: match row.get::<_, Option<i64>>(6)? {
+				Some(_) => Some(read_timestamp(row, 6)?),
+				None => None,
+			},
+			pinned: row.get(7)?,
+			archived: row.get(8)?,
+			uuid: row.get(9)?,
+			properties: properties::properties_from_json(&row.get::<_, String>(10)?),
+			notebook_id: row.get(11)?,
+		})
+	}
+
+	/// Converts a database row selected with [`SUMMARY_COLUMNS`] to a
+	/// NoteSummary.
+	fn row_to_summary(row: &rusqlite::Row) -> rusqlite::Result<NoteSummary> {
+		let tags_json: String = row.get(2)?;
+
+		#[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+		Ok(NoteSummary {
+			id:         row.get(0)?,
+			title:      row.get(1)?,
+			tags:       serde_json::from_str(&tags_json).unwrap_or_default(),
+			created_at: read_timestamp(row, 3)?,
+			updated_at: read_timestamp(row, 4)?,
+			deleted_at: match row.get::<_, Option<i64>>(5)? {
+				Some(_) => Some(read_timestamp(row, 5)?),
+				None => None,
+			},
+			pinned:     row.get(6)?,
+			archived:   row.get(7)?,
+			size:       row.get::<_, i64>(8)? as u64,
+			first_line: row.get(9)?,
+		})
+	}
+
+	/// Runs `f` inside a savepoint so its changes are committed or rolled back
+	/// as a unit. Savepoints nest, so `f` may call other transactional methods.
+	pub fn transaction<T>(&self, f: impl FnOnce(&Self) -> Result<T>) -> Result<T> {
+		self.conn.execute_batch("SAVEPOINT qnote_tx")?;
+		match f(self) {
+			Ok(value) => {
+				self.conn.execute_batch("RELEASE qnote_tx")?;
+				Ok(value)
+			}
+			Err(e) => {
+				self.conn.execute_batch("ROLLBACK TO qnote_tx; RELEASE qnote_tx")?;
+				Err(e)
+			}
+		}
+	}
+
+	/// Inserts a note and returns its assigned ID.
+	pub fn create_note(&self, note: &Note) -> Result<i64> {
+		self.transaction(|db| {
+			db.conn.execute(
+				"INSERT INTO notes (title, content, created_at, updated_at, uuid, notebook_id) VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+				params![
+					&note.title,
+					&note.content,
+					note.created_at.timestamp_millis(),
+					note.updated_at.timestamp_millis(),
+					&note.uuid,
+					note.notebook_id
+				],
+			)?;
+			let id = db.conn.last_insert_rowid();
+			db.set_note_tags(id, &note.tags)?;
+			db.set_note_properties(id, &note.properties)?;
+			db.set_note_links(id, &note.content)?;
+			db.set_note_tasks(id, &note.content)?;
+			Ok(id)
+		})
+	}
+
+	/// Inserts several notes in one transaction and returns their IDs in
+	/// order. If any insert fails, none of the notes are kept.
+	pub fn create_notes(&self, notes: &[Note]) -> Result<Vec<i64>> {
+		self.transaction(|db| notes.iter().map(|note| db.create_note(note)).collect())
+	}
+
+	/// Imports notes in one transaction, matching them to existing notes by
+	/// UUID: a note whose UUID is already in the database (trashed notes
+	/// included) overwrites its title, content, tags and properties, and any
+	/// other note is created. Returns what happened to each note, in order.
+	pub fn import_notes(&self, notes: &[Note]) -> Result<Vec<ImportOutcome>> {
+		self.transaction(|db| {
+			let mut outcomes = Vec::with_capacity(notes.len());
+			for note in notes {
+				let outcome = match db.get_note_by_uuid(&note.uuid)? {
+					Some(Note { id: Some(id), title, content, tags, properties, updated_at, .. }) => {
+						if title == note.title && content == note.content && note.has_tags(&tags) && properties == note.properties {
+							ImportOutcome::Unchanged(id)
+						} else {
+							db.update_note(id, Some(updated_at), &note.title, &note.content, &note.tags, Some(&note.properties))?;
+							ImportOutcome::Updated(id)
+						}
+					}
+					_ => ImportOutcome::Created(db.create_note(note)?),
+				};
+				outcomes.push(outcome);
+			}
+			Ok(outcomes)
+		})
+	}
+
+	/// Retrieves a note by ID, including notes in the trash.
+	pub fn get_note(&self, id: i64) -> Result<Option<Note>> {
+		let mut stmt = self.conn.prepare(&format!("SELECT {NOTE_COLUMNS} FROM notes n WHERE n.id = ?1"))?;
+
+		match stmt.query_row(params![id], Self::row_to_note) {
+			Ok(note) => Ok(Some(note)),
+			Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+			Err(e) => Err(e.into()),
+		}
+	}
+
+	/// Retrieves several notes by ID from one consistent snapshot, skipping IDs
+	/// that do not exist.
+	pub fn get_notes(&self, ids: &[i64]) -> Result<Vec<Note>> {
+		self.transaction(|db| {
+			Ok(ids.iter().map(|&id| db.get_note(id)).collect::<Result<Vec<_>>>()?.into_iter().flatten().collect())
+		})
+	}
+
+	/// Retrieves a note by UUID, including notes in the trash.
+	pub fn get_note_by_uuid(&self, uuid: &str) -> Result<Option<Note>> {
+		let mut stmt = self.conn.prepare(&format!("SELECT {NOTE_COLUMNS} FROM notes n WHERE n.uuid = ?1"))?;
+
+		match stmt.query_row(params![uuid], Self::row_to_note) {
+			Ok(note) => Ok(Some(note)),
+			Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+			Err(e) => Err(e.into()),
+		}
+	}
+
+	/// Returns summaries of notes outside the trash whose title contains
+	/// `pattern`, ignoring case, ordered by ID.
+	pub fn find_by_title(&self, pattern: &str) -> Result<Vec<NoteSummary>> {
+		// SQLite's lower() only folds ASCII, so titles are compared in Rust
+		let pattern = pattern.to_lowercase();
+		let mut stmt = self.conn.prepare("SELECT id, title FROM notes WHERE deleted_at IS NULL ORDER BY id")?;
+		let mut ids = Vec::new();
+		for row in stmt.query_map([], |row| Ok((row.get::<_, i64>(0)?, row.get::<_, String>(1)?)))? {
+			let (id, title) = row?;
+			if title.to_lowercase().contains(&pattern) {
+				ids.push(id);
+			}
+		}
+
+		let mut summary = self.conn.prepare_cached(&format!("SELECT {SUMMARY_COLUMNS} FROM notes n WHERE n.id = ?1"))?;
+		ids.into_iter().map(|id| Ok(summary.query_row(params![id], Self::row_to_summary)?)).collect()
+	}
+
+	/// Updates a note's title, content, tags and, if given, properties,
+	/// keeping the previous version in the note's revision history.
+	///
+	/// `read_at` is the `updated_at` of the note the new values are based on.
+	/// If the note has been updated since, nothing is written and an
+	/// [`EditConflict`] is returned. Pass None to overwrite it regardless.
+	pub fn update_note(
+		&self,
+		id: i64,
+		read_at: Option<DateTime<Utc>>,
+		title: &str,
+		content: &str,
+		tags: &[String],
+		properties: Option<&BTreeMap<String, PropertyValue>>,
+	) -> Result<()> {
+		self.transaction(|db| {
+			if let Some(previous) = db.get_note(id)? {
+				if read_at.is_some_and(|read_at| read_at != previous.updated_at) {
+					return Err(EditConflict { id }.into());
+				}
+				db.save_revision(&previous, title, content, tags, properties.unwrap_or(&previous.properties))?;
+			}
+			db.conn.execute("UPDATE notes SET title = ?1, content = ?2, updated_at = ?3 WHERE id = ?4", params![
+				title,
+				content,
+				Utc::now().timestamp_millis(),
+				id
+			])?;
+			db.set_note_tags(id, tags)?;
+			if let Some(properties) = properties {
+				db.set_note_properties(id, properties)?;
+			}
+			db.set_note_links(id, content)?;
+			db.set_note_tasks(id, content)
+		})
+	}
+
+	/// Marks notes as changed now, so that edits based on an earlier read of
+	/// them are caught as conflicts by [`update_note`](Self::update_note).
+	pub(super) fn touch_notes(&self, ids: &[i64]) -> Result<()> {
+		let mut touch = self.conn.prepare_cached("UPDATE notes SET updated_at = ?1 WHERE id = ?2")?;
+		let now = Utc::now().timestamp_millis();
+		for id in ids {
+			touch.execute(params![now, id])?;
+		}
+		Ok(())
+	}
+
+	/// Returns a number that changes whenever another connection commits to the
+	/// database, to notice changes made by other qnote processes.
+	pub fn data_version(&self) -> Result<i64> { Ok(self.conn.query_row("PRAGMA data_version", [], |row| row.get(0))?) }
+
+	/// Pins or unpins a note. Returns false if the note does not exist.
+	pub fn set_pinned(&self, id: i64, pinned: bool) -> Result<bool> {
+		Ok(self.conn.execute("UPDATE notes SET pinned = ?1 WHERE id = ?2", params![pinned, id])? > 0)
+	}
+
+	/// Archives or unarchives a note. Returns false if the note does not exist.
+	pub fn set_archived(&self, id: i64, archived: bool) -> Result<bool> {
+		Ok(self.conn.execute("UPDATE notes SET archived = ?1 WHERE id = ?2", params![archived, id])? > 0)
+	}
+
+	/// Computes note, tag and size totals without loading note contents.
+	/// Notes in the trash are not counted.
+	pub fn stats(&self) -> Result<NoteStats> {
+		let (note_count, total_bytes): (i64, i64) = self.conn.query_row(
+			"SELECT COUNT(*), ifnull(SUM(length(CAST(title AS BLOB)) + length(CAST(content AS BLOB))), 0)
+             FROM notes WHERE deleted_at IS NULL",
+			[],
+			|row| Ok((row.get(0)?, row.get(1)?)),
+		)?;
+		let tag_count: i64 = self.conn.query_row(
+			"SELECT COUNT(DISTINCT nt.tag_id) FROM note_tags nt JOIN notes n ON n.id = nt.note_id
+             WHERE n.deleted_at IS NULL",
+			[],
+			|row| row.get(0),
+		)?;
+
+		let title_and_date = |sql: &str| -> Result<Option<(String, DateTime<Utc>)>> {
+			match self.conn.query_row(sql, [], |row| Ok((row.get::<_, String>(0)?, read_timestamp(row, 1)?))) {
+				Ok(found) => Ok(Some(found)),
+				Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+				Err(e) => Err(e.into()),
+			}
+		};
+
+		#[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+		Ok(NoteStats {
+			note_count:  note_count as usize,
+			tag_count:   tag_count as usize,
+			total_bytes: total_bytes as usize,
+			oldest:      title_and_date(
+				"SELECT title, created_at FROM notes WHERE deleted_at IS NULL ORDER BY created_at ASC LIMIT 1",
+			)?,
+			newest:      title_and_date(
+				"SELECT title, updated_at FROM notes WHERE deleted_at IS NULL ORDER BY updated_at DESC LIMIT 1",
+			)?,
+		})
+	}
+}
+
+/// Reads a timestamp column, stored as milliseconds since the Unix epoch.
+fn read_timestamp(row: &rusqlite::Row, idx: usize) -> rusqlite::Result<DateTime<Utc>> {
+	let millis: i64 = row.get(idx)?;
+	DateTime::from_timestamp_millis(millis).ok_or(rusqlite::Error::IntegralValueOutOfRange(idx, millis))
+}
@@ -0,0 +1,187 @@
+//! Notebooks: a hierarchy of folders, each note filed in at most one.
+//!
+//! Notebooks are addressed by path, e.g. `Work/Project X` for the notebook
+//! `Project X` inside `Work`.
+
+use std::collections::HashMap;
+
+use anyhow::Result;
+use rusqlite::params;
+
+use super::Database;
+
+/// A notebook in the notebook hierarchy.
+#[derive(Debug, Clone)]
+pub struct NotebookNode {
+	pub id:       i64,
+	pub name:     String,
+	/// The full path, e.g. `Work/Project X`.
+	pub path:     String,
+	/// Notes filed in this notebook or any notebook below it.
+	pub count:    usize,
+	pub children: Vec<NotebookNode>,
+}
+
+/// SQL selecting the ID of a notebook and of every notebook below it. Takes
+/// the notebook's ID as one parameter.
+pub(super) const NOTEBOOK_AND_DESCENDANTS: &str = "WITH RECURSIVE below(id) AS (
+        SELECT ? UNION ALL SELECT nb.id FROM notebooks nb JOIN below ON nb.parent_id = below.id
+    ) SELECT id FROM below";
+
+/// Splits a notebook path into its names, ignoring stray slashes and spaces.
+fn path_segments(path: &str) -> Result<Vec<&str>> {
+	let segments: Vec<&str> = path.split('/').map(str::trim).filter(|segment| !segment.is_empty()).collect();
+	if segments.is_empty() {
+		anyhow::bail!("The notebook path is empty");
+	}
+	Ok(segments)
+}
+
+/// Builds the notebooks below `parent` (None for the top level), sorted by
+/// name, from each notebook's `(id, name)` by parent and its own note count.
+fn notebook_children(
+	parent: Option<i64>,
+	parent_path: &str,
+	by_parent: &HashMap<Option<i64>, Vec<(i64, String)>>,
+	counts: &HashMap<i64, usize>,
+) -> Vec<NotebookNode> {
+	let mut children: Vec<NotebookNode> = by_parent
+		.get(&parent)
+		.into_iter()
+		.flatten()
+		.map(|(id, name)| {
+			let path = if parent_path.is_empty() { name.clone() } else { format!("{parent_path}/{name}") };
+			let children = notebook_children(Some(*id), &path, by_parent, counts);
+			let count = counts.get(id).copied().unwrap_or(0) + children.iter().map(|child| child.count).sum::<usize>();
+			NotebookNode { id: *id, name: name.clone(), path, count, children }
+		})
+		.collect();
+	children.sort_by_cached_key(|node| node.name.to_lowercase());
+	children
+}
+
+impl Database {
+	/// Creates a notebook by path, along with any missing notebooks above it,
+	/// and returns its ID. Fails if the notebook already exists.
+	pub fn create_notebook(&self, path: &str) -> Result<i64> {
+		let segments = path_segments(path)?;
+		if self.find_notebook(path)?.is_some() {
+			anyhow::bail!("Notebook '{}' already exists", segments.join("/"));
+		}
+
+		self.transaction(|db| {
+			let mut parent = None;
+			for name in segments {
+				parent = Some(match db.child_notebook(parent, name)? {
+					Some(id) => id,
+					None => {
+						db.conn.execute("INSERT INTO notebooks (name, parent_id) VALUES (?1, ?2)", params![name, parent])?;
+						db.conn.last_insert_rowid()
+					}
+				});
+			}
+			Ok(parent.unwrap_or_default())
+		})
+	}
+
+	/// Looks up a notebook's ID by path.
+	pub fn find_notebook(&self, path: &str) -> Result<Option<i64>> {
+		let mut parent = None;
+		for name in path_segments(path)? {
+			match self.child_notebook(parent, name)? {
+				Some(id) => parent = Some(id),
+				None => return Ok(None),
+			}
+		}
+		Ok(parent)
+	}
+
+	/// Returns the full path of a notebook, or None if it does not exist.
+	pub fn notebook_path(&self, id: i64) -> Result<Option<String>> {
+		let mut names = Vec::new();
+		let mut current = Some(id);
+		while let Some(id) = current {
+			match self.conn.query_row("SELECT name, parent_id FROM notebooks WHERE id = ?1", params![id], |row| {
+				Ok((row.get::<_, String>(0)?, row.get::<_, Option<i64>>(1)?))
+			}) {
+				Ok((name, parent)) => {
+					names.push(name);
+					current = parent;
+				}
+				Err(rusqlite::Error::QueryReturnedNoRows) => return Ok(None),
+				Err(e) => return Err(e.into()),
+			}
+		}
+		names.reverse();
+		Ok(Some(names.join("/")))
+	}
+
+	/// Returns the notebook hierarchy with note counts. A notebook's count
+	/// includes the notes in the notebooks below it. Notes in the trash are not
+	/// counted.
+	pub fn notebook_tree(&self) -> Result<Vec<NotebookNode>> {
+		let mut stmt = self.conn.prepare("SELECT id, name, parent_id FROM notebooks")?;
+		let rows =
+			stmt.query_map([], |row| Ok((row.get::<_, i64>(0)?, row.get::<_, String>(1)?, row.get::<_, Option<i64>>(2)?)))?;
+		let mut by_parent: HashMap<Option<i64>, Vec<(i64, String)>> = HashMap::new();
+		for row in rows {
+			let (id, name, parent) = row?;
+			by_parent.entry(parent).or_default().push((id, name));
+		}
+
+		let mut stmt = self.conn.prepare(
+			"SELECT notebook_id, COUNT(*) FROM notes WHERE notebook_id IS NOT NULL AND deleted_at IS NULL GROUP BY notebook_id",
+		)?;
+		#[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+		let counts = stmt
+			.query_map([], |row| Ok((row.get::<_, i64>(0)?, row.get::<_, i64>(1)? as usize)))?
+			.collect::<Result<HashMap<_, _>, _>>()?;
+
+		Ok(notebook_children(None, "", &by_parent, &counts))
+	}
+
+	/// Files several notes in a notebook (or in none) in one transaction.
+	/// Returns the number of notes that moved.
+	pub fn move_notes_to_notebook(&self, note_ids: &[i64], notebook_id: Option<i64>) -> Result<usize> {
+		self.transaction(|db| {
+			let mut move_note =
+				db.conn.prepare_cached("UPDATE notes SET notebook_id = ?1 WHERE id = ?2 AND notebook_id IS NOT ?1")?;
+			let mut moved = Vec::new();
+			for &note_id in note_ids {
+				if move_note.execute(params![notebook_id, note_id])? > 0 {
+					moved.push(note_id);
+				}
+			}
+			db.touch_notes(&moved)?;
+			Ok(moved.len())
+		})
+	}
+
+	/// Deletes a notebook and the notebooks below it. Their notes are kept,
+	/// outside any notebook. Returns the number of notes taken out.
+	pub fn delete_notebook(&self, id: i64) -> Result<usize> {
+		self.transaction(|db| {
+			let affected = db.conn.query_row(
+				&format!("SELECT COUNT(*) FROM notes WHERE notebook_id IN ({NOTEBOOK_AND_DESCENDANTS})"),
+				params![id],
+				|row| row.get::<_, i64>(0),
+			)?;
+			db.conn.execute("DELETE FROM notebooks WHERE id = ?1", params![id])?;
+			#[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+			Ok(affected as usize)
+		})
+	}
+
+	/// Looks up the notebook called `name` directly inside `parent`.
+	fn child_notebook(&self, parent: Option<i64>, name: &str) -> Result<Option<i64>> {
+		match self.conn.query_row(
+			"SELECT id FROM notebooks WHERE parent_id IS ?1 AND name = ?2",
+			params![parent, name],
+			|row| row.get(0),
+		) {
+			Ok(id) => Ok(Some(id)),
+			Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+			Err(e) => Err(e.into()),
+		}
+	}
+}
@@ -0,0 +1,172 @@
+//! Typed key/value properties on notes, stored in `note_properties`.
+
+use std::{collections::BTreeMap, fmt};
+
+use anyhow::Result;
+use chrono::NaiveDate;
+use rusqlite::params;
+
+use super::Database;
+
+/// Property names reserved for qnote's own frontmatter fields.
+const RESERVED_KEYS: &[&str] = &["uuid", "tags"];
+
+/// The value of a note property. Its type is inferred from how it is written:
+/// `true`/`false`, numbers and `YYYY-MM-DD` dates are recognized, anything else
+/// (or anything in double quotes) is text. Numbers that would not be written
+/// back the same way, like `01234` or `1.10`, stay text.
+#[derive(Debug, Clone, PartialEq)]
+pub enum PropertyValue {
+	Text(String),
+	Number(f64),
+	Bool(bool),
+	Date(NaiveDate),
+}
+
+impl PropertyValue {
+	/// Parses a value as written on the command line or in frontmatter.
+	pub fn parse(input: &str) -> Self {
+		let input = input.trim();
+		if let Some(quoted) = input.strip_prefix('"').and_then(|rest| rest.strip_suffix('"')) {
+			return Self::Text(quoted.to_string());
+		}
+		match input {
+			"true" => Self::Bool(true),
+			"false" => Self::Bool(false),
+			_ => {
+				if let Ok(number) = input.parse::<f64>()
+					&& number.is_finite()
+					&& number.to_string() == input
+				{
+					Self::Number(number)
+				} else if let Ok(date) = NaiveDate::parse_from_str(input, "%Y-%m-%d") {
+					Self::Date(date)
+				} else {
+					Self::Text(input.to_string())
+				}
+			}
+		}
+	}
+
+	/// Name of the type, as stored in the `kind` column.
+	pub fn kind(&self) -> &'static str {
+		match self {
+			Self::Text(_) => "text",
+			Self::Number(_) => "number",
+			Self::Bool(_) => "bool",
+			Self::Date(_) => "date",
+		}
+	}
+
+	/// Reads a value stored as `kind` and its text form.
+	fn from_stored(kind: &str, value: &str) -> Self {
+		match kind {
+			"text" => Self::Text(value.to_string()),
+			_ => Self::parse(value),
+		}
+	}
+
+	/// The value written so that [`PropertyValue::parse`] reads it back as the
+	/// same type, quoting text that would otherwise be taken for another type.
+	pub fn to_source(&self) -> String {
+		let plain = self.to_string();
+		if matches!(self, Self::Text(_)) && (Self::parse(&plain) != *self || plain.starts_with('"')) {
+			format!("\"{plain}\"")
+		} else {
+			plain
+		}
+	}
+}
+
+impl fmt::Display for PropertyValue {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			Self::Text(text) => f.write_str(text),
+			Self::Number(number) => write!(f, "{number}"),
+			Self::Bool(value) => write!(f, "{value}"),
+			Self::Date(date) => write!(f, "{}", date.format("%Y-%m-%d")),
+		}
+	}
+}
+
+/// Checks that a property name can be written as a frontmatter key.
+pub fn validate_property_key(key: &str) -> Result<()> {
+	if key.is_empty() || !key.chars().all(|c| c.is_alphanumeric() || c == '_' || c == '-') {
+		anyhow::bail!("Property names may only contain letters, digits, '-' and '_'");
+	}
+	if RESERVED_KEYS.contains(&key) {
+		anyhow::bail!("'{key}' is reserved and cannot be used as a property name");
+	}
+	Ok(())
+}
+
+/// Decodes the JSON object built by the properties column of `NOTE_COLUMNS`,
+/// mapping each key to `[kind, value]`.
+pub(super) fn properties_from_json(json: &str) -> BTreeMap<String, PropertyValue> {
+	let pairs: BTreeMap<String, (String, String)> = serde_json::from_str(json).unwrap_or_default();
+	pairs.into_iter().map(|(key, (kind, value))| (key, PropertyValue::from_stored(&kind, &value))).collect()
+}
+
+/// Encodes properties as the JSON object [`properties_from_json`] reads.
+pub(super) fn properties_to_json(properties: &BTreeMap<String, PropertyValue>) -> Result<String> {
+	let pairs: BTreeMap<&str, (&str, String)> =
+		properties.iter().map(|(key, value)| (key.as_str(), (value.kind(), value.to_string()))).collect();
+	Ok(serde_json::to_string(&pairs)?)
+}
+
+impl Database {
+	/// Replaces all properties of a note.
+	pub(super) fn set_note_properties(&self, note_id: i64, properties: &BTreeMap<String, PropertyValue>) -> Result<()> {
+		self.transaction(|db| {
+			db.conn.execute("DELETE FROM note_properties WHERE note_id = ?1", params![note_id])?;
+			let mut insert =
+				db.conn.prepare_cached("INSERT INTO note_properties (note_id, key, kind, value) VALUES (?1, ?2, ?3, ?4)")?;
+			for (key, value) in properties {
+				insert.execute(params![note_id, key, value.kind(), value.to_string()])?;
+			}
+			Ok(())
+		})
+	}
+
+	/// Sets one property of a note, replacing any previous value, and marks the
+	/// note as updated. The previous version is kept in the note's history.
+	pub fn set_property(&self, note_id: i64, key: &str, value: &PropertyValue) -> Result<()> {
+		validate_property_key(key)?;
+		self.transaction(|db| {
+			let previous = db.get_notes(&[note_id])?;
+			db.conn.execute(
+				"INSERT INTO note_properties (note_id, key, kind, value) VALUES (?1, ?2, ?3, ?4)
+                 ON CONFLICT (note_id, key) DO UPDATE SET kind = excluded.kind, value = excluded.value",
+				params![note_id, key, value.kind(), value.to_string()],
+			)?;
+			db.keep_revisions(&previous)?;
+			db.touch_notes(&[note_id])
+		})
+	}
+
+	/// Removes a property from a note. Returns false if it was not set. The
+	/// previous version is kept in the note's history.
+	pub fn remove_property(&self, note_id: i64, key: &str) -> Result<bool> {
+		self.transaction(|db| {
+			let previous = db.get_notes(&[note_id])?;
+			let removed =
+				db.conn.execute("DELETE FROM note_properties WHERE note_id = ?1 AND key = ?2", params![note_id, key])? > 0;
+			if removed {
+				db.keep_revisions(&previous)?;
+				db.touch_notes(&[note_id])?;
+			}
+			Ok(removed)
+		})
+	}
+
+	/// Returns every property name with the number of live notes using it.
+	pub fn property_counts(&self) -> Result<Vec<(String, usize)>> {
+		let mut stmt = self.conn.prepare(
+			"SELECT p.key, COUNT(*) FROM note_properties p JOIN notes n ON n.id = p.note_id
+             WHERE n.deleted_at IS NULL GROUP BY p.key ORDER BY p.key",
+		)?;
+		#[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+		let counts = stmt.query_map([], |row| Ok((row.get(0)?, row.get::<_, i64>(1)? as usize)))?;
+		Ok(counts.collect::<Result<Vec<_>, _>>()?)
+	}
+}
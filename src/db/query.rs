@@ -0,0 +1,248 @@
+//! Note listings whose filters, order and paging are compiled to SQL.
+
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use rusqlite::{ToSql, params_from_iter};
+
+use super::{Database, NoteSummary, PropertyValue, SUMMARY_COLUMNS, notebooks::NOTEBOOK_AND_DESCENDANTS, search::{build_match_expr, map_fts_error}, tags::tag_or_descendant};
+
+/// Query parameters, in the order of their placeholders.
+pub(super) type SqlParams = Vec<Box<dyn ToSql>>;
+
+/// Field a note listing is ordered by.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SortKey {
+	Updated,
+	Created,
+	Title,
+}
+
+/// A note listing: which notes to include, in what order, and which page.
+///
+/// Built by chaining methods from [`NoteQuery::new`], which lists every live,
+/// unarchived note, most recently updated first. Pinned notes always come
+/// first whatever the sort order.
+#[derive(Debug, Clone)]
+pub struct NoteQuery {
+	tags_any:         Vec<String>,
+	tags_all:         Vec<String>,
+	properties:       Vec<(String, PropertyValue)>,
+	notebook:         Option<i64>,
+	created_after:    Option<DateTime<Utc>>,
+	created_before:   Option<DateTime<Utc>>,
+	updated_after:    Option<DateTime<Utc>>,
+	updated_before:   Option<DateTime<Utc>>,
+	text:             Option<String>,
+	include_archived: bool,
+	sort:             SortKey,
+	descending:       bool,
+	limit:            Option<usize>,
+	offset:           usize,
+}
+
+impl Default for NoteQuery {
+	fn default() -> Self {
+		Self {
+			tags_any:         Vec::new(),
+			tags_all:         Vec::new(),
+			properties:       Vec::new(),
+			notebook:         None,
+			created_after:    None,
+			created_before:   None,
+			updated_after:    None,
+			updated_before:   None,
+			text:             None,
+			include_archived: false,
+			sort:             SortKey::Updated,
+			descending:       true,
+			limit:            None,
+			offset:           0,
+		}
+	}
+}
+
+impl NoteQuery {
+	pub fn new() -> Self { Self::default() }
+
+	/// Keeps notes carrying at least one of `tags` (no effect if empty). A tag
+	/// also matches the tags nested below it: `work` matches `work/infra`.
+	pub fn tags_any(mut self, tags: impl IntoIterator<Item = String>) -> Self {
+		self.tags_any.extend(tags);
+		self
+	}
+
+	/// Keeps notes carrying every one of `tags` (or a tag nested below it).
+	pub fn tags_all(mut self, tags: impl IntoIterator<Item = String>) -> Self {
+		self.tags_all.extend(tags);
+		self
+	}
+
+	/// Keeps notes whose property `key` equals `value`.
+	pub fn property(mut self, key: impl Into<String>, value: PropertyValue) -> Self {
+		self.properties.push((key.into(), value));
+		self
+	}
+
+	/// Keeps notes filed in the notebook with this ID or a notebook below it
+	/// (no effect if None).
+	pub fn notebook(mut self, notebook_id: Option<i64>) -> Self {
+		self.notebook = notebook_id;
+		self
+	}
+
+	/// Keeps notes created at or after `after` and before `before`.
+	pub fn created_between(mut self, after: Option<DateTime<Utc>>, before: Option<DateTime<Utc>>) -> Self {
+		self.created_after = after;
+		self.created_before = before;
+		self
+	}
+
+	/// Keeps notes last updated at or after `after` and before `before`.
+	pub fn updated_between(mut self, after: Option<DateTime<Utc>>, before: Option<DateTime<Utc>>) -> Self {
+		self.updated_after = after;
+		self.updated_before = before;
+		self
+	}
+
+	/// Keeps notes matching a search query (same syntax as `search_notes`).
+	pub fn text(mut self, query: impl Into<String>) -> Self {
+		self.text = Some(query.into());
+		self
+	}
+
+	/// Lists archived notes too.
+	pub fn include_archived(mut self, include: bool) -> Self {
+		self.include_archived = include;
+		self
+	}
+
+	/// Orders by `key`, newest or Z→A first if `descending`.
+	pub fn sort(mut self, key: SortKey, descending: bool) -> Self {
+		self.sort = key;
+		self.descending = descending;
+		self
+	}
+
+	/// Returns at most `limit` notes (all if None).
+	pub fn limit(mut self, limit: Option<usize>) -> Self {
+		self.limit = limit;
+		self
+	}
+
+	/// Skips the first `offset` notes.
+	pub fn offset(mut self, offset: usize) -> Self {
+		self.offset = offset;
+		self
+	}
+
+	/// Compiles the filters (everything but order and paging) to conditions on
+	/// `notes n`, to be joined with AND, and their parameters in order.
+	pub(super) fn conditions(&self) -> Result<(Vec<String>, SqlParams)> {
+		let mut conditions = vec!["n.deleted_at IS NULL".to_string()];
+		let mut params: SqlParams = Vec::new();
+		let has_tag = "EXISTS (SELECT 1 FROM note_tags nt JOIN tags t ON t.id = nt.tag_id
+                      WHERE nt.note_id = n.id AND";
+
+		if !self.include_archived {
+			conditions.push("n.archived = 0".to_string());
+		}
+		if !self.tags_any.is_empty() {
+			let matches = vec![tag_or_descendant("t.name"); self.tags_any.len()].join(" OR ");
+			conditions.push(format!("{has_tag} ({matches}))"));
+			for tag in &self.tags_any {
+				params.extend(tag_params(tag));
+			}
+		}
+		for tag in &self.tags_all {
+			conditions.push(format!("{has_tag} {})", tag_or_descendant("t.name")));
+			params.extend(tag_params(tag));
+		}
+		if let Some(notebook_id) = self.notebook {
+			conditions.push(format!("n.notebook_id IN ({NOTEBOOK_AND_DESCENDANTS})"));
+			params.push(Box::new(notebook_id));
+		}
+		for (key, value) in &self.properties {
+			conditions.push(
+				"EXISTS (SELECT 1 FROM note_properties p WHERE p.note_id = n.id AND p.key = ? AND p.value = ?)".to_string(),
+			);
+			params.push(Box::new(key.clone()));
+			params.push(Box::new(value.to_string()));
+		}
+
+		let ranges = [
+			("n.created_at", ">=", self.created_after),
+			("n.created_at", "<", self.created_before),
+			("n.updated_at", ">=", self.updated_after),
+			("n.updated_at", "<", self.updated_before),
+		];
+		for (column, op, bound) in ranges {
+			if let Some(bound) = bound {
+				conditions.push(format!("{column} {op} ?"));
+				params.push(Box::new(bound.timestamp_millis()));
+			}
+		}
+
+		if let Some(text) = &self.text {
+			let match_expr = build_match_expr(text)?;
+			if !match_expr.is_empty() {
+				conditions.push("n.id IN (SELECT rowid FROM notes_fts WHERE notes_fts MATCH ?)".to_string());
+				params.push(Box::new(match_expr));
+			}
+		}
+
+		Ok((conditions, params))
+	}
+
+	/// Compiles the query to a SELECT of [`SUMMARY_COLUMNS`] and its
+	/// parameters.
+	fn to_sql(&self) -> Result<(String, SqlParams)> {
+		let (conditions, mut params) = self.conditions()?;
+		let direction = if self.descending { "DESC" } else { "ASC" };
+		let column = match self.sort {
+			SortKey::Updated => "n.updated_at",
+			SortKey::Created => "n.created_at",
+			SortKey::Title => "n.title COLLATE NOCASE",
+		};
+		params.push(Box::new(self.limit.and_then(|l| i64::try_from(l).ok()).unwrap_or(-1)));
+		params.push(Box::new(i64::try_from(self.offset).unwrap_or(i64::MAX)));
+
+		let sql = format!(
+			"SELECT {SUMMARY_COLUMNS} FROM notes n
+             WHERE {}
+             ORDER BY n.pinned DESC, {column} {direction}, n.id {direction}
+             LIMIT ? OFFSET ?",
+			conditions.join(" AND ")
+		);
+		Ok((sql, params))
+	}
+}
+
+/// Parameters for one [`tag_or_descendant`] condition.
+fn tag_params(tag: &str) -> [Box<dyn ToSql>; 3] { [(); 3].map(|()| Box::new(tag.to_string()) as Box<dyn ToSql>) }
+
+impl Database {
+	/// Returns summaries of the notes selected by `query`. Returns a
+	/// [`QueryError`](super::QueryError) if its text filter is invalid.
+	pub fn query_notes(&self, query: &NoteQuery) -> Result<Vec<NoteSummary>> {
+		let (sql, params) = query.to_sql()?;
+		let mut stmt = self.conn.prepare(&sql)?;
+		let summaries = stmt
+			.query_map(params_from_iter(params.iter()), Self::row_to_summary)
+			.and_then(Iterator::collect::<rusqlite::Result<Vec<_>>>);
+		summaries.map_err(map_fts_error)
+	}
+
+	/// Counts the notes selected by `query`, ignoring its paging.
+	pub fn count_notes(&self, query: &NoteQuery) -> Result<usize> {
+		let (conditions, params) = query.conditions()?;
+		let count: i64 = self
+			.conn
+			.query_row(
+				&format!("SELECT count(*) FROM notes n WHERE {}", conditions.join(" AND ")),
+				params_from_iter(params.iter()),
+				|row| row.get(0),
+			)
+			.map_err(map_fts_error)?;
+		Ok(usize::try_from(count).unwrap_or_default())
+	}
+}
@@ -0,0 +1,145 @@
+//! Note revision history: every update keeps the version it replaces.
+
+use std::collections::BTreeMap;
+
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use rusqlite::params;
+
+use super::{Database, Note, PropertyValue, properties, read_timestamp};
+
+/// A previous version of a note.
+#[derive(Debug, Clone)]
+pub struct Revision {
+	pub id:         i64,
+	pub note_id:    i64,
+	pub title:      String,
+	pub content:    String,
+	pub tags:       Vec<String>,
+	/// The note's properties, or None for revisions saved before properties
+	/// were kept.
+	pub properties: Option<BTreeMap<String, PropertyValue>>,
+	/// When this version was last saved (the note's `updated_at` at the time).
+	pub saved_at:   DateTime<Utc>,
+}
+
+impl Revision {
+	/// Returns this version as a note; both timestamps are set to `saved_at`,
+	/// the UUID is left empty and unrecorded properties are empty.
+	pub fn to_note(&self) -> Note {
+		Note {
+			id:          Some(self.note_id),
+			title:       self.title.clone(),
+			content:     self.content.clone(),
+			tags:        self.tags.clone(),
+			created_at:  self.saved_at,
+			updated_at:  self.saved_at,
+			deleted_at:  None,
+			pinned:      false,
+			archived:    false,
+			uuid:        String::new(),
+			properties:  self.properties.clone().unwrap_or_default(),
+			notebook_id: None,
+		}
+	}
+
+	/// Converts a database row to a Revision.
+	fn from_row(row: &rusqlite::Row) -> rusqlite::Result<Self> {
+		let tags_json: String = row.get(4)?;
+		Ok(Self {
+			id:         row.get(0)?,
+			note_id:    row.get(1)?,
+			title:      row.get(2)?,
+			content:    row.get(3)?,
+			tags:       serde_json::from_str(&tags_json).unwrap_or_default(),
+			saved_at:   read_timestamp(row, 5)?,
+			properties: row.get::<_, Option<String>>(6)?.as_deref().map(properties::properties_from_json),
+		})
+	}
+}
+
+impl Database {
+	/// Stores `previous` as a revision unless the new values are identical,
+	/// then prunes the note's history down to the configured limit.
+	pub(super) fn save_revision(
+		&self,
+		previous: &Note,
+		title: &str,
+		content: &str,
+		tags: &[String],
+		properties: &BTreeMap<String, PropertyValue>,
+	) -> Result<()> {
+		let Some(note_id) = previous.id else {
+			return Ok(());
+		};
+		if previous.title == title
+			&& previous.content == content
+			&& previous.has_tags(tags)
+			&& previous.properties == *properties
+		{
+			return Ok(());
+		}
+
+		self.conn.execute(
+			"INSERT INTO note_revisions (note_id, title, content, tags, properties, saved_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+			params![
+				note_id,
+				&previous.title,
+				&previous.content,
+				serde_json::to_string(&previous.tags)?,
+				properties::properties_to_json(&previous.properties)?,
+				previous.updated_at.timestamp_millis()
+			],
+		)?;
+
+		if self.max_revisions > 0 {
+			#[allow(clippy::cast_possible_wrap)]
+			self.conn.execute(
+				"DELETE FROM note_revisions WHERE note_id = ?1 AND id NOT IN (
+                    SELECT id FROM note_revisions WHERE note_id = ?1 ORDER BY id DESC LIMIT ?2
+                )",
+				params![note_id, self.max_revisions as i64],
+			)?;
+		}
+		Ok(())
+	}
+
+	/// Stores each of `previous` as a revision if the note has changed since,
+	/// for edits that change notes directly rather than through
+	/// [`update_note`](Self::update_note).
+	pub(super) fn keep_revisions(&self, previous: &[Note]) -> Result<()> {
+		for note in previous {
+			if let Some(current) = note.id.map(|id| self.get_note(id)).transpose()?.flatten() {
+				self.save_revision(note, &current.title, &current.content, &current.tags, &current.properties)?;
+			}
+		}
+		Ok(())
+	}
+
+	/// Returns a note's revisions, newest first.
+	pub fn list_revisions(&self, note_id: i64) -> Result<Vec<Revision>> {
+		let mut stmt = self.conn.prepare(
+			"SELECT id, note_id, title, content, tags, saved_at, properties FROM note_revisions
+             WHERE note_id = ?1 ORDER BY id DESC",
+		)?;
+
+		Ok(stmt.query_map(params![note_id], Revision::from_row)?.collect::<Result<Vec<_>, _>>()?)
+	}
+
+	/// Retrieves a revision of a specific note.
+	pub fn get_revision(&self, note_id: i64, revision_id: i64) -> Result<Option<Revision>> {
+		let result = self.conn.query_row(
+			"SELECT id, note_id, title, content, tags, saved_at, properties FROM note_revisions
+             WHERE id = ?1 AND note_id = ?2",
+			params![revision_id, note_id],
+			Revision::from_row,
+		);
+
+		match result {
+			Ok(revision) => Ok(Some(revision)),
+			Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+			Err(e) => Err(e.into()),
+		}
+	}
+}
@@ -0,0 +1,250 @@
+//! Full-text search over the FTS5 index.
+//!
+//! User queries are translated into FTS5 MATCH expressions. Supported syntax:
+//! - bare words match as prefixes (`depl` finds "deploy")
+//! - `"exact phrase"`, optionally followed by `*` for a prefix phrase
+//! - `AND`, `OR`, `NOT` (uppercase) and parentheses for grouping
+//! - `NEAR(a b, 5)` for terms within a number of tokens of each other
+//! - `title:`, `content:` and `tags:` to restrict a term to one field
+
+use std::fmt;
+
+use anyhow::Result;
+use rusqlite::{ToSql, params_from_iter};
+
+use super::{Database, NoteQuery, NoteSummary, SUMMARY_COLUMN_COUNT, SUMMARY_COLUMNS, query::SqlParams};
+
+/// A search hit with a highlighted excerpt of the best-matching column.
+#[derive(Debug, Clone)]
+pub struct SearchResult {
+	pub note:          NoteSummary,
+	pub snippet:       String,
+	/// Character indices of matched terms in the note title.
+	pub title_matches: Vec<usize>,
+}
+
+/// A search query that could not be understood.
+#[derive(Debug)]
+pub struct QueryError(String);
+
+impl fmt::Display for QueryError {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result { write!(f, "Invalid search query: {}", self.0) }
+}
+
+impl std::error::Error for QueryError {}
+
+/// Markers wrapped around matched terms in search snippets.
+pub const SNIPPET_MATCH_START: &str = "**";
+pub const SNIPPET_MATCH_END: &str = "**";
+
+/// Markers used internally to locate matches in highlighted titles.
+const TITLE_MATCH_START: char = '\u{2}';
+const TITLE_MATCH_END: char = '\u{3}';
+
+/// bm25 column weights for (title, content, tags); title hits rank highest.
+const BM25_WEIGHTS: (f64, f64, f64) = (10.0, 1.0, 5.0);
+
+/// Maximum number of tokens in a search snippet.
+const SNIPPET_TOKENS: i64 = 16;
+
+/// Columns that can be targeted with `column:term` filters.
+const COLUMNS: [&str; 3] = ["title", "content", "tags"];
+
+impl Database {
+	/// Searches notes through the FTS5 index, best matches first.
+	///
+	/// Results are ranked with bm25 (title matches weigh most) and carry a
+	/// snippet with the matched terms highlighted. Returns a [`QueryError`] if
+	/// the query syntax is invalid. Only notes kept by the filters of `filter`
+	/// are searched; its order and paging are ignored.
+	pub fn search_notes(&self, query: &str, filter: &NoteQuery) -> Result<Vec<SearchResult>> {
+		let match_expr = build_match_expr(query)?;
+		if match_expr.is_empty() {
+			return Ok(
+				self
+					.query_notes(filter)?
+					.into_iter()
+					.map(|note| SearchResult { note, snippet: String::new(), title_matches: Vec::new() })
+					.collect(),
+			);
+		}
+
+		let (title_weight, content_weight, tags_weight) = BM25_WEIGHTS;
+		let (conditions, filter_params) = filter.conditions()?;
+		let mut stmt = self.conn.prepare(&format!(
+			"SELECT {SUMMARY_COLUMNS},
+                        snippet(notes_fts, -1, ?, ?, '…', ?),
+                        highlight(notes_fts, 0, ?, ?)
+                 FROM notes_fts
+                 JOIN notes n ON n.id = notes_fts.rowid
+                 WHERE notes_fts MATCH ? AND {}
+                 ORDER BY bm25(notes_fts, ?, ?, ?)",
+			conditions.join(" AND ")
+		))?;
+
+		let mut params: SqlParams = vec![
+			Box::new(SNIPPET_MATCH_START),
+			Box::new(SNIPPET_MATCH_END),
+			Box::new(SNIPPET_TOKENS),
+			Box::new(TITLE_MATCH_START.to_string()),
+			Box::new(TITLE_MATCH_END.to_string()),
+			Box::new(match_expr),
+		];
+		params.extend(filter_params);
+		params.extend([title_weight, content_weight, tags_weight].map(|w| Box::new(w) as Box<dyn ToSql>));
+
+		let results = stmt
+			.query_map(params_from_iter(params.iter()), |row| {
+				Ok(SearchResult {
+					note:          Self::row_to_summary(row)?,
+					snippet:       row.get(SUMMARY_COLUMN_COUNT)?,
+					title_matches: highlighted_indices(&row.get::<_, String>(SUMMARY_COLUMN_COUNT + 1)?),
+				})
+			})
+			.and_then(Iterator::collect::<rusqlite::Result<Vec<_>>>);
+
+		results.map_err(map_fts_error)
+	}
+}
+
+/// Turns FTS5 syntax errors into a [`QueryError`].
+pub(super) fn map_fts_error(e: rusqlite::Error) -> anyhow::Error {
+	match e {
+		rusqlite::Error::SqliteFailure(_, Some(msg)) if msg.starts_with("fts5:") => {
+			let detail = match msg.split_once("syntax error near ") {
+				Some((_, "\"\"")) => "query ends unexpectedly".to_string(),
+				Some((_, near)) => format!("unexpected {}", near.replace('"', "'")),
+				None => msg.trim_start_matches("fts5:").trim().to_string(),
+			};
+			QueryError(detail).into()
+		}
+		e => e.into(),
+	}
+}
+
+/// Translates a user query into an FTS5 MATCH expression.
+///
+/// Words are quoted so punctuation can never break the FTS5 syntax, and every
+/// bare word becomes a prefix term. Operators, phrases, groups and column
+/// filters are passed through after checking that quotes and parentheses are
+/// balanced.
+pub(super) fn build_match_expr(query: &str) -> Result<String, QueryError> {
+	let mut terms: Vec<String> = Vec::new();
+	// One entry per open parenthesis: whether it belongs to a NEAR group
+	let mut groups: Vec<bool> = Vec::new();
+	let mut chars = query.chars().peekable();
+
+	while let Some(&ch) = chars.peek() {
+		match ch {
+			c if c.is_whitespace() => {
+				chars.next();
+			}
+			'"' => {
+				chars.next();
+				let mut phrase = String::new();
+				loop {
+					match chars.next() {
+						Some('"') if chars.peek() == Some(&'"') => {
+							chars.next();
+							phrase.push_str("\"\"");
+						}
+						Some('"') => break,
+						Some(c) => phrase.push(c),
+						None => return Err(QueryError("missing closing quote".to_string())),
+					}
+				}
+				let prefix = if chars.peek() == Some(&'*') {
+					chars.next();
+					"*"
+				} else {
+					""
+				};
+				terms.push(format!("\"{phrase}\"{prefix}"));
+			}
+			'(' => {
+				chars.next();
+				groups.push(terms.last().is_some_and(|t| t == "NEAR"));
+				terms.push("(".to_string());
+			}
+			')' => {
+				chars.next();
+				if groups.pop().is_none() {
+					return Err(QueryError("unexpected ')'".to_string()));
+				}
+				terms.push(")".to_string());
+			}
+			',' => {
+				chars.next();
+				terms.push(",".to_string());
+			}
+			_ => {
+				let mut word = String::new();
+				while let Some(&c) = chars.peek() {
+					if c.is_whitespace() || matches!(c, '"' | '(' | ')' | ',') {
+						break;
+					}
+					word.push(c);
+					chars.next();
+				}
+
+				if let Some((column, rest)) = word.split_once(':')
+					&& COLUMNS.contains(&column.to_lowercase().as_str())
+				{
+					terms.push(format!("{} :", column.to_lowercase()));
+					if !rest.is_empty() {
+						terms.extend(prefix_term(rest));
+					} else if chars.peek().is_none_or(|c| c.is_whitespace()) {
+						return Err(QueryError(format!("'{column}:' needs a term after it")));
+					}
+				} else if word == "NOT" && terms.last().is_some_and(|t| t == "AND") {
+					// FTS5's NOT is binary ("a NOT b"), so accept the familiar "a AND NOT b"
+					terms.pop();
+					terms.push(word);
+				} else if matches!(word.as_str(), "AND" | "OR" | "NOT")
+					|| (word == "NEAR" && chars.peek() == Some(&'('))
+					|| (groups.last() == Some(&true)
+						&& terms.last().is_some_and(|t| t == ",")
+						&& word.chars().all(|c| c.is_ascii_digit()))
+				{
+					terms.push(word);
+				} else {
+					terms.extend(prefix_term(&word));
+				}
+			}
+		}
+	}
+
+	if !groups.is_empty() {
+		return Err(QueryError("missing closing ')'".to_string()));
+	}
+
+	Ok(terms.join(" "))
+}
+
+/// Quotes a bare word as an FTS5 prefix term (a trailing `*` is optional).
+fn prefix_term(word: &str) -> Option<String> {
+	let word = word.trim_end_matches('*');
+	(!word.is_empty()).then(|| format!("\"{}\"*", word.replace('"', "\"\"")))
+}
+
+/// Returns the character indices enclosed by the title highlight markers.
+fn highlighted_indices(highlighted: &str) -> Vec<usize> {
+	let mut indices = Vec::new();
+	let mut in_match = false;
+	let mut idx = 0;
+
+	for ch in highlighted.chars() {
+		match ch {
+			TITLE_MATCH_START => in_match = true,
+			TITLE_MATCH_END => in_match = false,
+			_ => {
+				if in_match {
+					indices.push(idx);
+				}
+				idx += 1;
+			}
+		}
+	}
+
+	indices
+}
@@ -0,0 +1,259 @@
+//! Tag storage in the normalized `tags`/`note_tags` tables.
+//!
+//! Tags nest with `/`: `work/infra/k8s` sits below `work/infra` and `work`,
+//! whether or not those tags are used themselves.
+
+use std::collections::{BTreeMap, HashSet};
+
+use anyhow::Result;
+use rusqlite::{params, params_from_iter};
+
+use super::Database;
+
+/// A tag in the tag hierarchy.
+#[derive(Debug, Clone)]
+pub struct TagNode {
+	/// Last segment of the tag, e.g. `k8s` for `work/infra/k8s`.
+	pub name:     String,
+	/// The full tag.
+	pub path:     String,
+	/// Notes using this tag or any tag below it.
+	pub count:    usize,
+	pub children: Vec<TagNode>,
+}
+
+/// SQL condition matching a tag or any tag nested below it. Takes the tag as
+/// three parameters.
+pub(super) fn tag_or_descendant(column: &str) -> String {
+	format!("({column} = ? OR substr({column}, 1, length(?) + 1) = ? || '/')")
+}
+
+/// Builds the children of `parent` (None for the top level) from the notes
+/// using each tag path, sorted by name.
+fn tag_children(parent: Option<&str>, notes_by_path: &BTreeMap<String, HashSet<i64>>) -> Vec<TagNode> {
+	let mut children: Vec<TagNode> = notes_by_path
+		.iter()
+		.filter(|(path, _)| path.rsplit_once('/').map(|(p, _)| p) == parent)
+		.map(|(path, notes)| TagNode {
+			name:     path.rsplit('/').next().unwrap_or(path).to_string(),
+			path:     path.clone(),
+			count:    notes.len(),
+			children: tag_children(Some(path), notes_by_path),
+		})
+		.collect();
+	children.sort_by_cached_key(|node| node.name.to_lowercase());
+	children
+}
+
+impl Database {
+	/// Replaces the tags attached to a note.
+	pub(super) fn set_note_tags(&self, note_id: i64, tags: &[String]) -> Result<()> {
+		self.conn.execute("DELETE FROM note_tags WHERE note_id = ?1", params![note_id])?;
+
+		let mut insert_tag = self.conn.prepare_cached("INSERT OR IGNORE INTO tags (name) VALUES (?1)")?;
+		let mut link_tag = self
+			.conn
+			.prepare_cached("INSERT OR IGNORE INTO note_tags (note_id, tag_id) SELECT ?1, id FROM tags WHERE name = ?2")?;
+		for tag in tags {
+			insert_tag.execute(params![tag])?;
+			link_tag.execute(params![note_id, tag])?;
+		}
+		Ok(())
+	}
+
+	/// Returns every tag with the number of notes using it, most used first.
+	/// Notes in the trash are not counted.
+	pub fn tag_counts(&self) -> Result<Vec<(String, usize)>> {
+		let mut stmt = self.conn.prepare(
+			"SELECT t.name, COUNT(nt.note_id) AS uses FROM tags t
+             JOIN note_tags nt ON nt.tag_id = t.id
+             JOIN notes n ON n.id = nt.note_id
+             WHERE n.deleted_at IS NULL
+             GROUP BY t.id
+             ORDER BY uses DESC, t.name ASC",
+		)?;
+
+		#[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+		let counts = stmt.query_map([], |row| Ok((row.get(0)?, row.get::<_, i64>(1)? as usize)))?;
+		Ok(counts.collect::<Result<Vec<_>, _>>()?)
+	}
+
+	/// Returns the tag hierarchy with note counts. A parent's count includes
+	/// the notes tagged with any tag below it. Notes in the trash are not
+	/// counted.
+	pub fn tag_tree(&self) -> Result<Vec<TagNode>> {
+		let mut stmt = self.conn.prepare(
+			"SELECT t.name, nt.note_id FROM tags t
+             JOIN note_tags nt ON nt.tag_id = t.id
+             JOIN notes n ON n.id = nt.note_id
+             WHERE n.deleted_at IS NULL",
+		)?;
+		let rows = stmt.query_map([], |row| Ok((row.get::<_, String>(0)?, row.get::<_, i64>(1)?)))?;
+
+		let mut notes_by_path: BTreeMap<String, HashSet<i64>> = BTreeMap::new();
+		for row in rows {
+			let (name, note_id) = row?;
+			let mut end = 0;
+			for segment in name.split('/') {
+				end += segment.len();
+				notes_by_path.entry(name[..end].to_string()).or_default().insert(note_id);
+				end += 1;
+			}
+		}
+		Ok(tag_children(None, &notes_by_path))
+	}
+
+	/// Renames a tag on every note that has it, merging into `new` if that tag
+	/// already exists. Tags nested below `old` move along, so renaming `work`
+	/// to `job` turns `work/infra` into `job/infra`. Returns the number of
+	/// notes affected.
+	pub fn rename_tag(&self, old: &str, new: &str) -> Result<usize> { self.merge_tags(&[old], new) }
+
+	/// Merges several tags into `into` on every note, as if each were renamed
+	/// to it with [`rename_tag`](Self::rename_tag), in one transaction, keeping
+	/// each affected note's previous version as a revision. Returns the number
+	/// of notes affected.
+	pub fn merge_tags(&self, sources: &[&str], into: &str) -> Result<usize> {
+		let sources: Vec<&str> = sources.iter().copied().filter(|&source| source != into).collect();
+		if let Some(source) = sources.iter().find(|source| into.starts_with(&format!("{source}/"))) {
+			anyhow::bail!("Cannot move '{source}' below itself");
+		}
+
+		self.transaction(|db| {
+			let affected = db.tagged_notes(&sources)?;
+			let previous = db.get_notes(&affected)?;
+			let condition = tag_or_descendant("name");
+			for source in &sources {
+				let mut stmt = db.conn.prepare(&format!("SELECT id, name FROM tags WHERE {condition}"))?;
+				let tags = stmt
+					.query_map(params![source, source, source], |row| Ok((row.get::<_, i64>(0)?, row.get::<_, String>(1)?)))?
+					.collect::<Result<Vec<_>, _>>()?;
+				for (old_id, name) in tags {
+					db.move_tag(old_id, &format!("{into}{}", &name[source.len()..]))?;
+				}
+			}
+			db.keep_revisions(&previous)?;
+			db.touch_notes(&affected)?;
+			Ok(affected.len())
+		})
+	}
+
+	/// Removes a tag and the tags nested below it from every note, keeping each
+	/// affected note's previous version as a revision. Returns the number of
+	/// notes affected.
+	pub fn delete_tag(&self, tag: &str) -> Result<usize> {
+		self.transaction(|db| {
+			let affected = db.tagged_notes(&[tag])?;
+			let previous = db.get_notes(&affected)?;
+			let condition = tag_or_descendant("name");
+			db.conn
+				.execute(&format!("DELETE FROM note_tags WHERE tag_id IN (SELECT id FROM tags WHERE {condition})"), params![
+					tag, tag, tag
+				])?;
+			db.conn.execute(&format!("DELETE FROM tags WHERE {condition}"), params![tag, tag, tag])?;
+			db.keep_revisions(&previous)?;
+			db.touch_notes(&affected)?;
+			Ok(affected.len())
+		})
+	}
+
+	/// Returns the existing tags that are `tag` or nested below it, by name.
+	pub fn tags_under(&self, tag: &str) -> Result<Vec<String>> {
+		let mut stmt =
+			self.conn.prepare(&format!("SELECT name FROM tags WHERE {} ORDER BY name", tag_or_descendant("name")))?;
+		let names = stmt.query_map(params![tag, tag, tag], |row| row.get(0))?;
+		Ok(names.collect::<Result<Vec<_>, _>>()?)
+	}
+
+	/// Returns the IDs of the notes, trashed ones included, carrying any of
+	/// `tags` or a tag nested below one of them, in ascending order. These are
+	/// the notes a rename, merge or delete of `tags` changes.
+	pub fn tagged_notes(&self, tags: &[&str]) -> Result<Vec<i64>> {
+		if tags.is_empty() {
+			return Ok(Vec::new());
+		}
+		let condition = vec![tag_or_descendant("t.name"); tags.len()].join(" OR ");
+		let mut stmt = self.conn.prepare(&format!(
+			"SELECT DISTINCT nt.note_id FROM note_tags nt JOIN tags t ON t.id = nt.tag_id WHERE {condition}
+             ORDER BY nt.note_id"
+		))?;
+		let ids = stmt.query_map(params_from_iter(tags.iter().flat_map(|tag| [*tag; 3])), |row| row.get(0))?;
+		Ok(ids.collect::<Result<Vec<_>, _>>()?)
+	}
+
+	/// Gives a tag a new name, merging it into the tag of that name if there is
+	/// one.
+	fn move_tag(&self, old_id: i64, new: &str) -> Result<()> {
+		match self.tag_id(new)? {
+			Some(new_id) if new_id == old_id => {}
+			Some(new_id) => {
+				self.conn.execute(
+					"INSERT OR IGNORE INTO note_tags (note_id, tag_id) SELECT note_id, ?2 FROM note_tags WHERE tag_id = ?1",
+					params![old_id, new_id],
+				)?;
+				self.conn.execute("DELETE FROM tags WHERE id = ?1", params![old_id])?;
+			}
+			None => {
+				self.conn.execute("UPDATE tags SET name = ?2 WHERE id = ?1", params![old_id, new])?;
+			}
+		}
+		Ok(())
+	}
+
+	/// Adds a tag to several notes in one transaction. Returns the number of
+	/// notes that did not have it yet.
+	pub fn add_tag_to_notes(&self, note_ids: &[i64], tag: &str) -> Result<usize> {
+		self.transaction(|db| {
+			let previous = db.get_notes(note_ids)?;
+			db.conn.execute("INSERT OR IGNORE INTO tags (name) VALUES (?1)", params![tag])?;
+			let mut link_tag = db
+				.conn
+				.prepare_cached("INSERT OR IGNORE INTO note_tags (note_id, tag_id) SELECT ?1, id FROM tags WHERE name = ?2")?;
+			let mut added = Vec::new();
+			for &note_id in note_ids {
+				if link_tag.execute(params![note_id, tag])? > 0 {
+					added.push(note_id);
+				}
+			}
+			db.keep_revisions(&previous)?;
+			db.touch_notes(&added)?;
+			if added.is_empty() {
+				// Drop the tag again if it was only created for this call
+				db.conn.execute(
+					"DELETE FROM tags WHERE name = ?1 AND NOT EXISTS (SELECT 1 FROM note_tags WHERE tag_id = tags.id)",
+					params![tag],
+				)?;
+			}
+			Ok(added.len())
+		})
+	}
+
+	/// Removes a tag from several notes in one transaction. Returns the number
+	/// of notes that had it.
+	pub fn remove_tag_from_notes(&self, note_ids: &[i64], tag: &str) -> Result<usize> {
+		self.transaction(|db| {
+			let previous = db.get_notes(note_ids)?;
+			let mut unlink_tag = db.conn.prepare_cached(
+				"DELETE FROM note_tags WHERE note_id = ?1 AND tag_id = (SELECT id FROM tags WHERE name = ?2)",
+			)?;
+			let mut removed = Vec::new();
+			for &note_id in note_ids {
+				if unlink_tag.execute(params![note_id, tag])? > 0 {
+					removed.push(note_id);
+				}
+			}
+			db.keep_revisions(&previous)?;
+			db.touch_notes(&removed)?;
+			Ok(removed.len())
+		})
+	}
+
+	/// Looks up a tag's row ID by exact name.
+	fn tag_id(&self, name: &str) -> Result<Option<i64>> {
+		match self.conn.query_row("SELECT id FROM tags WHERE name = ?1", params![name], |row| row.get(0)) {
+			Ok(id) => Ok(Some(id)),
+			Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+			Err(e) => Err(e.into()),
+		}
+	}
+}
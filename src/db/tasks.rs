@@ -0,0 +1,82 @@
+//! Task list items (`- [ ]`) found in notes, stored in `note_tasks`.
+
+use anyhow::Result;
+use chrono::NaiveDate;
+use rusqlite::{params, params_from_iter};
+
+use super::{Database, NoteQuery};
+use crate::utils::{extract_tasks, set_task_checkbox};
+
+/// A task list item and the note it is written in.
+#[derive(Debug, Clone)]
+pub struct Task {
+	pub note_id:    i64,
+	pub note_title: String,
+	/// 1-based line number within the note content.
+	pub line:       usize,
+	pub text:       String,
+	pub done:       bool,
+	pub due:        Option<NaiveDate>,
+}
+
+impl Database {
+	/// Replaces a note's indexed tasks with the ones found in `content`.
+	pub(super) fn set_note_tasks(&self, note_id: i64, content: &str) -> Result<()> {
+		self.conn.execute("DELETE FROM note_tasks WHERE note_id = ?1", params![note_id])?;
+
+		let mut insert = self
+			.conn
+			.prepare_cached("INSERT INTO note_tasks (note_id, line, text, done, due) VALUES (?1, ?2, ?3, ?4, ?5)")?;
+		for task in extract_tasks(content) {
+			let due = task.due.map(|date| date.format("%Y-%m-%d").to_string());
+			insert.execute(params![note_id, task.line, task.text, task.done, due])?;
+		}
+		Ok(())
+	}
+
+	/// Returns the tasks in the notes kept by the filters of `notes`, grouped
+	/// by note in listing order and then by line. Leaves out checked tasks if
+	/// `open_only`.
+	pub fn tasks(&self, notes: &NoteQuery, open_only: bool) -> Result<Vec<Task>> {
+		let (mut conditions, params) = notes.conditions()?;
+		if open_only {
+			conditions.push("t.done = 0".to_string());
+		}
+		let mut stmt = self.conn.prepare(&format!(
+			"SELECT t.note_id, n.title, t.line, t.text, t.done, t.due
+             FROM note_tasks t JOIN notes n ON n.id = t.note_id
+             WHERE {}
+             ORDER BY n.pinned DESC, n.updated_at DESC, n.id, t.line",
+			conditions.join(" AND ")
+		))?;
+
+		let tasks = stmt.query_map(params_from_iter(params.iter()), |row| {
+			Ok(Task {
+				note_id:    row.get(0)?,
+				note_title: row.get(1)?,
+				line:       row.get(2)?,
+				text:       row.get(3)?,
+				done:       row.get(4)?,
+				due:        row.get::<_, Option<String>>(5)?.and_then(|due| NaiveDate::parse_from_str(&due, "%Y-%m-%d").ok()),
+			})
+		})?;
+		Ok(tasks.collect::<Result<Vec<_>, _>>()?)
+	}
+
+	/// Checks or unchecks the task on `line` of a note by rewriting its
+	/// checkbox, which saves a new version of the note. Returns false if the
+	/// task was already in that state.
+	pub fn set_task_done(&self, note_id: i64, line: usize, done: bool) -> Result<bool> {
+		let Some(note) = self.get_note(note_id)? else { anyhow::bail!("Note with ID {note_id} not found") };
+		let Some(task) = extract_tasks(&note.content).into_iter().find(|task| task.line == line) else {
+			anyhow::bail!("Line {line} of note {note_id} is not a task");
+		};
+		if task.done == done {
+			return Ok(false);
+		}
+
+		let content = set_task_checkbox(&note.content, line, done).unwrap_or(note.content);
+		self.update_note(note_id, Some(note.updated_at), &note.title, &content, &note.tags, None)?;
+		Ok(true)
+	}
+}
@@ -0,0 +1,62 @@
+//! Soft delete: notes move to the trash before they are removed for good.
+
+use anyhow::Result;
+use chrono::{TimeDelta, Utc};
+use rusqlite::params;
+
+use super::{Database, NoteSummary, SUMMARY_COLUMNS};
+
+impl Database {
+	/// Moves a note to the trash. Returns false if it was not found or is
+	/// already trashed.
+	pub fn trash_note(&self, id: i64) -> Result<bool> {
+		let changed =
+			self.conn.execute("UPDATE notes SET deleted_at = ?1 WHERE id = ?2 AND deleted_at IS NULL", params![
+				Utc::now().timestamp_millis(),
+				id
+			])?;
+		Ok(changed > 0)
+	}
+
+	/// Moves several notes to the trash in one transaction and returns how
+	/// many were moved.
+	pub fn trash_notes(&self, ids: &[i64]) -> Result<usize> {
+		self.transaction(|db| ids.iter().try_fold(0, |count, &id| Ok(count + usize::from(db.trash_note(id)?))))
+	}
+
+	/// Moves a note out of the trash. Returns false if it was not in the trash.
+	pub fn restore_note(&self, id: i64) -> Result<bool> {
+		let changed =
+			self.conn.execute("UPDATE notes SET deleted_at = NULL WHERE id = ?1 AND deleted_at IS NOT NULL", params![id])?;
+		Ok(changed > 0)
+	}
+
+	/// Permanently deletes a note that is in the trash.
+	pub fn purge_note(&self, id: i64) -> Result<bool> {
+		let changed = self.conn.execute("DELETE FROM notes WHERE id = ?1 AND deleted_at IS NOT NULL", params![id])?;
+		Ok(changed > 0)
+	}
+
+	/// Returns summaries of trashed notes, most recently deleted first.
+	pub fn list_trash(&self) -> Result<Vec<NoteSummary>> {
+		let mut stmt = self.conn.prepare(&format!(
+			"SELECT {SUMMARY_COLUMNS} FROM notes n WHERE n.deleted_at IS NOT NULL ORDER BY n.deleted_at DESC"
+		))?;
+
+		Ok(stmt.query_map([], Self::row_to_summary)?.collect::<Result<Vec<_>, _>>()?)
+	}
+
+	/// Permanently deletes every note in the trash and returns how many.
+	pub fn empty_trash(&self) -> Result<usize> {
+		Ok(self.conn.execute("DELETE FROM notes WHERE deleted_at IS NOT NULL", [])?)
+	}
+
+	/// Permanently deletes notes that have been in the trash for more than
+	/// `days` days and returns how many.
+	pub fn purge_trash_older_than(&self, days: u32) -> Result<usize> {
+		let cutoff = Utc::now() - TimeDelta::days(i64::from(days));
+		Ok(self.conn.execute("DELETE FROM notes WHERE deleted_at IS NOT NULL AND deleted_at < ?1", params![
+			cutoff.timestamp_millis()
+		])?)
+	}
+}
@@ -0,0 +1,32 @@
+//! qnote's note storage and markdown parsing, as used by the `qnote`
+//! command-line and terminal interfaces.
+//!
+//! - [`db`]: the SQLite note database — notes, tags, notebooks, properties,
+//!   tasks, links, revisions, the trash, full-text search and backups, all
+//!   through [`Database`], and importing notes by UUID
+//!   ([`Database::import_notes`]).
+//! - [`utils`]: reading and writing notes as markdown ([`parse_markdown_file`],
+//!   [`note_to_markdown`]) and the parsing behind it.
+//! - [`config`]: [`DatabaseConfig`](config::DatabaseConfig) and the helpers
+//!   that locate database files. The rest of the configuration file (UI,
+//!   keybindings, theme) belongs to the `qnote` binary.
+//!
+//! ```
+//! use qnote::{Database, Note, NoteQuery, config::DatabaseConfig};
+//!
+//! let db = Database::new(":memory:", &DatabaseConfig::default())?;
+//! let id = db.create_note(&Note::new("Standup".into(), "Deploy at noon".into(), vec!["work".into()]))?;
+//!
+//! let listed = db.query_notes(&NoteQuery::new().tags_any(["work".to_string()]))?;
+//! assert_eq!(listed[0].id, id);
+//! let found = db.search_notes("deploy", &NoteQuery::new())?;
+//! assert_eq!(found[0].note.title, "Standup");
+//! # anyhow::Ok(())
+//! ```
+
+pub mod config;
+pub mod db;
+pub mod utils;
+
+pub use db::{Database, Note, NoteQuery, NoteSummary, PropertyValue, SearchResult};
+pub use utils::{ParsedNote, note_to_markdown, parse_markdown_file};
@@ -1,41 +1,60 @@
 mod cli;
 mod commands;
-mod config;
-mod db;
+mod settings;
 mod tui;
-mod utils;
-
-use std::path::PathBuf;
 
 use anyhow::{Context, Result};
 use clap::Parser;
 use cli::{Cli, Commands};
-use commands::handle_command;
-use config::Config;
-use db::Database;
-
-/// Returns platform-specific database path, creating directory if needed.
-fn get_db_path() -> Result<PathBuf> {
-	let mut path = dirs::data_local_dir().unwrap_or_else(|| PathBuf::from("."));
-	path.push("qnote");
-	std::fs::create_dir_all(&path).context("Failed to create qnote data directory")?;
-	path.push("notes.db");
-	Ok(path)
-}
+use commands::{auto_backup, handle_backup, handle_command, handle_decrypt, handle_encrypt, handle_restore_backup, handle_vault, open_database};
+use qnote::config;
+use settings::Config;
 
 fn main() -> Result<()> {
 	// Load configuration
 	let config = Config::load().context("Failed to load configuration")?;
 	config.validate().context("Invalid configuration")?;
 
-	let db_path = get_db_path()?;
-	let db_path_str = db_path.to_str().ok_or_else(|| anyhow::anyhow!("Invalid database path"))?;
-	let db = Database::new(db_path_str, &config.database)?;
 	let cli = Cli::parse();
+	let db_path = config.resolve_db_path(cli.db.as_deref(), cli.vault.as_deref())?;
+
+	if let Some(Commands::Vault { command }) = cli.command {
+		return handle_vault(config, &db_path, command);
+	}
+
+	if let Some(Commands::RestoreBackup { file, yes }) = &cli.command {
+		return handle_restore_backup(&config, &db_path, file, *yes);
+	}
+
+	config::ensure_parent_dir(&db_path)?;
+	let db_path_str = db_path.to_str().ok_or_else(|| anyhow::anyhow!("Invalid database path"))?;
+
+	// Maintenance commands inspect the schema before anything is migrated
+	let db = open_database(db_path_str, &config, !matches!(cli.command, Some(Commands::Db { .. })))?;
 
 	match cli.command {
-		Some(Commands::Tui) | None => tui::run_tui(db, config)?,
-		Some(cmd) => handle_command(&db, cmd)?,
+		Some(Commands::Tui) | None => {
+			let db = tui::run_tui(db, config.clone())?;
+			db.flush()?;
+			if let Some(copy) = db.conflict_copy() {
+				eprintln!(
+					"Warning: another qnote process changed the vault during this session. Your changes are in {}; \
+					 open it with --db to merge them.",
+					copy.display()
+				);
+			}
+			auto_backup(&db, &config, &db_path, true);
+		}
+		Some(Commands::Encrypt) => handle_encrypt(db, db_path_str)?,
+		Some(Commands::Decrypt) => handle_decrypt(db, db_path_str)?,
+		Some(Commands::Backup { to }) => handle_backup(&db, &config, &db_path, to)?,
+		Some(cmd) => {
+			// Save whatever the command changed, even if it then failed
+			let result = handle_command(&db, cmd);
+			db.flush()?;
+			result?;
+			auto_backup(&db, &config, &db_path, false);
+		}
 	}
 
 	Ok(())
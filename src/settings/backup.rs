@@ -0,0 +1,30 @@
+use serde::{Deserialize, Serialize};
+
+/// When `qnote` backs up the database by itself.
+pub const AUTO_BACKUP_MODES: [&str; 3] = ["off", "exit", "daily"];
+
+/// Backup configuration.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BackupConfig {
+	/// Directory backups are written to (defaults to backups/ in the platform
+	/// data directory)
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub dir: Option<String>,
+
+	/// Backups kept per database; older ones are deleted (0 = keep all)
+	#[serde(default = "default_keep")]
+	pub keep: usize,
+
+	/// Automatic backups: off, exit (whenever the TUI closes) or daily (at most
+	/// once a day, after any command)
+	#[serde(default = "default_auto")]
+	pub auto: String,
+}
+
+const fn default_keep() -> usize { 10 }
+
+fn default_auto() -> String { "off".to_string() }
+
+impl Default for BackupConfig {
+	fn default() -> Self { Self { dir: None, keep: default_keep(), auto: default_auto() } }
+}
@@ -9,7 +9,8 @@ pub struct EditorConfig {
 	#[serde(default)]
 	pub default_editor: Option<String>,
 
-	/// Whether to create secure temp files (Unix only)
+	/// Whether to create secure temp files (Unix only). Encrypted vaults
+	/// always use them.
 	#[serde(default = "default_true")]
 	pub secure_temp_files: bool,
 }
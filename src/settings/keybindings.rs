@@ -46,6 +46,38 @@ pub struct KeybindingsConfig {
 	/// Key to move up
 	#[serde(default = "default_move_up_key")]
 	pub move_up: char,
+
+	/// Key to open or close the trash view
+	#[serde(default = "default_trash_key")]
+	pub trash: char,
+
+	/// Key to restore a note from the trash
+	#[serde(default = "default_restore_key")]
+	pub restore: char,
+
+	/// Key to pin or unpin a note
+	#[serde(default = "default_pin_key")]
+	pub pin: char,
+
+	/// Key to archive or unarchive a note
+	#[serde(default = "default_archive_key")]
+	pub archive: char,
+
+	/// Key to open the note behind the highlighted `[[link]]`
+	#[serde(default = "default_follow_link_key")]
+	pub follow_link: char,
+
+	/// Key to open or close the tasks view
+	#[serde(default = "default_tasks_key")]
+	pub tasks: char,
+
+	/// Key to open or close the tag tree
+	#[serde(default = "default_tags_key")]
+	pub tags: char,
+
+	/// Key to open or close the notebook tree
+	#[serde(default = "default_notebooks_key")]
+	pub notebooks: char,
 }
 
 const fn default_quit_key() -> char { 'q' }
@@ -70,6 +102,22 @@ const fn default_move_down_key() -> char { 'j' }
 
 const fn default_move_up_key() -> char { 'k' }
 
+const fn default_trash_key() -> char { 't' }
+
+const fn default_restore_key() -> char { 'r' }
+
+const fn default_pin_key() -> char { 'p' }
+
+const fn default_archive_key() -> char { 'z' }
+
+const fn default_follow_link_key() -> char { 'f' }
+
+const fn default_tasks_key() -> char { 'c' }
+
+const fn default_tags_key() -> char { '#' }
+
+const fn default_notebooks_key() -> char { 'b' }
+
 impl Default for KeybindingsConfig {
 	fn default() -> Self {
 		Self {
@@ -84,6 +132,14 @@ impl Default for KeybindingsConfig {
 			goto_bottom: default_goto_bottom_key(),
 			move_down:   default_move_down_key(),
 			move_up:     default_move_up_key(),
+			trash:       default_trash_key(),
+			restore:     default_restore_key(),
+			pin:         default_pin_key(),
+			archive:     default_archive_key(),
+			follow_link: default_follow_link_key(),
+			tasks:       default_tasks_key(),
+			tags:        default_tags_key(),
+			notebooks:   default_notebooks_key(),
 		}
 	}
 }
@@ -0,0 +1,49 @@
+use std::{env, path::PathBuf};
+
+use anyhow::Result;
+use qnote::config::{DB_ENV_VAR, default_db_path, expand_home};
+use serde::{Deserialize, Serialize};
+
+use super::Config;
+
+/// A named database, selected with `--vault <name>`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VaultConfig {
+	/// Path to the vault's database file
+	pub path: String,
+}
+
+impl Config {
+	/// Returns the database to open. In order of precedence: `--db`,
+	/// `--vault`, `$QNOTE_DB`, the vault chosen with `qnote vault switch`,
+	/// `database.path`, and finally `notes.db` in the platform data directory.
+	pub fn resolve_db_path(&self, db: Option<&str>, vault: Option<&str>) -> Result<PathBuf> {
+		if let Some(path) = db {
+			return Ok(expand_home(path));
+		}
+		if let Some(name) = vault {
+			return self.vault_path(name);
+		}
+		if let Ok(path) = env::var(DB_ENV_VAR)
+			&& !path.is_empty()
+		{
+			return Ok(expand_home(&path));
+		}
+		if let Some(name) = &self.default_vault {
+			return self.vault_path(name);
+		}
+		match &self.database.path {
+			Some(path) => Ok(expand_home(path)),
+			None => default_db_path(),
+		}
+	}
+
+	/// Returns the database path of a named vault.
+	pub fn vault_path(&self, name: &str) -> Result<PathBuf> {
+		self
+			.vaults
+			.get(name)
+			.map(|vault| expand_home(&vault.path))
+			.ok_or_else(|| anyhow::anyhow!("Unknown vault '{name}' (see `qnote vault list`)"))
+	}
+}
@@ -0,0 +1,87 @@
+use anyhow::Result;
+use qnote::{db::{EditConflict, Note}, utils::ParsedNote};
+use ratatui::crossterm::event::KeyCode;
+
+use super::{App, Screen};
+
+/// An edit that was not saved because the note changed while it was open in
+/// the editor.
+pub struct PendingEdit {
+	pub id:     i64,
+	pub parsed: ParsedNote,
+}
+
+impl App {
+	/// Saves an edit made in the external editor, or asks how to resolve it if
+	/// the note was changed elsewhere in the meantime.
+	pub(super) fn save_edit(&mut self, note: &Note, parsed: ParsedNote) -> Result<()> {
+		let Some(id) = note.id else { return Ok(()) };
+		let saved = self.db.transaction(|db| {
+			db.update_note(id, Some(note.updated_at), &parsed.title, &parsed.content, &parsed.tags, Some(&parsed.properties))
+		});
+		match saved {
+			Ok(()) => {
+				self.set_message("Note saved");
+				self.refresh_notes()
+			}
+			Err(e) if e.is::<EditConflict>() => {
+				self.conflict = Some(PendingEdit { id, parsed });
+				self.screen = Screen::Conflict;
+				self.set_message(format!("'{}' was changed elsewhere while you edited it", note.title));
+				Ok(())
+			}
+			Err(e) => Err(e),
+		}
+	}
+
+	/// Handles keys while an edit conflict waits to be resolved.
+	pub fn handle_conflict_input(&mut self, key: KeyCode) -> Result<bool> {
+		let msg = match key {
+			KeyCode::Char('m') => "Saved your version (theirs is in the note's history)",
+			KeyCode::Char('t') => "Kept their version; your edit was discarded",
+			KeyCode::Char('c') => "Saved your version as a new note",
+			_ => return Ok(false),
+		};
+		let Some(PendingEdit { id, parsed }) = self.conflict.take() else {
+			self.screen = Screen::List;
+			return Ok(false);
+		};
+
+		match key {
+			KeyCode::Char('m') => self.db.transaction(|db| {
+				db.update_note(id, None, &parsed.title, &parsed.content, &parsed.tags, Some(&parsed.properties))
+			})?,
+			KeyCode::Char('c') => {
+				let title = format!("{} (conflicted copy)", parsed.title);
+				let mut note = ParsedNote { title, ..parsed }.into_note();
+				note.notebook_id = self.db.get_note(id)?.and_then(|original| original.notebook_id);
+				self.db.create_note(&note)?;
+			}
+			_ => {}
+		}
+		self.screen = Screen::List;
+		self.set_message(msg);
+		self.refresh_notes()?;
+		Ok(false)
+	}
+
+	/// Refreshes the list if another process changed the database, keeping the
+	/// same note selected.
+	pub fn check_external_changes(&mut self) -> Result<()> {
+		let version = self.db.data_version()?;
+		if version == self.data_version {
+			return Ok(());
+		}
+		self.data_version = version;
+
+		if self.screen == Screen::Tasks {
+			self.refresh_tasks()?;
+		}
+		let selected = self.get_selected_note().map(|note| note.id);
+		self.refresh_notes()?;
+		if let Some(index) = selected.and_then(|id| self.notes.iter().position(|note| note.id == id)) {
+			self.list_state.select(Some(index));
+		}
+		Ok(())
+	}
+}
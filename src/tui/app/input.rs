@@ -1,8 +1,9 @@
 use anyhow::Result;
+use qnote::utils::{normalize_tag, note_to_markdown, sanitize_filename};
 use ratatui::crossterm::event::{KeyCode, KeyModifiers};
 
-use super::{App, Screen, selection};
-use crate::{db::Note, tui::editor::{open_editor_for_edit, open_editor_for_new_note}, utils::{note_to_markdown, sanitize_filename}};
+use super::{App, ConfirmAction, Screen};
+use crate::tui::editor::{open_editor_for_edit, open_editor_for_new_note};
 
 impl App {
 	#[allow(clippy::too_many_lines)]
@@ -10,6 +11,7 @@ impl App {
 		if modifiers.contains(KeyModifiers::SHIFT) {
 			return match key {
 				KeyCode::Char('A') => {
+					self.load_all_notes()?;
 					let count = self.selection.select_all(&self.notes);
 					self.set_message(format!("Selected {count} notes"));
 					Ok(false)
@@ -25,9 +27,23 @@ impl App {
 					if self.selection.is_empty() {
 						self.set_message("No notes selected");
 					} else {
-						let count = self.selection.delete_all(&self.db)?;
-						self.set_message(format!("Deleted {count} notes"));
-						self.refresh_notes()?;
+						let count = self.selection.len();
+						self.request_confirm(ConfirmAction::TrashSelected, format!("Move {count} notes to trash?"));
+					}
+					Ok(false)
+				}
+				KeyCode::Char('Z') => {
+					self.show_archived = !self.show_archived;
+					self.refresh_notes()?;
+					self.set_message(if self.show_archived { "Showing archived notes" } else { "Hiding archived notes" });
+					Ok(false)
+				}
+				KeyCode::Char(c @ ('T' | 'U')) => {
+					if self.selection.is_empty() {
+						self.set_message("No notes selected");
+					} else {
+						self.tag_input.clear();
+						self.screen = Screen::TagInput { add: c == 'T' };
 					}
 					Ok(false)
 				}
@@ -35,12 +51,11 @@ impl App {
 					if self.selection.is_empty() {
 						self.set_message("No notes selected");
 					} else {
-						let (success, errors) = self.selection.export_all(&self.notes);
-						self.set_message(if errors == 0 {
-							format!("Exported {success} notes")
-						} else {
-							format!("Exported {success} notes ({errors} failed)")
-						});
+						let msg = match self.selection.export_all(&self.db) {
+							Ok(count) => format!("Exported {count} notes"),
+							Err(e) => format!("Export failed, nothing written: {e}"),
+						};
+						self.set_message(msg);
 					}
 					Ok(false)
 				}
@@ -64,20 +79,25 @@ impl App {
 		}
 
 		match key {
+			KeyCode::Tab => self.cycle_link(true),
+			KeyCode::BackTab => self.cycle_link(false),
+			KeyCode::Char(c) if c == self.config.keybindings.follow_link => self.follow_link()?,
+			KeyCode::Esc if self.link_cursor.take().is_some() => {}
 			KeyCode::Char(' ') => {
-				selection::toggle_and_navigate(
-					&mut self.selection,
-					&mut self.list_state,
-					&self.notes,
-					&mut self.preview_scroll,
-				);
+				if let Some(id) = self.get_selected_note().map(|note| note.id) {
+					self.selection.toggle(id);
+					self.navigate(true)?;
+				}
 			}
 			KeyCode::Char('.') => self.help_expanded = !self.help_expanded,
 			KeyCode::Char(c) if c == self.config.keybindings.quit => return Ok(true),
 			KeyCode::Char(c) if c == self.config.keybindings.new_note || c == 'a' => {
-				let msg = match open_editor_for_new_note(&self.config.editor) {
-					Ok(Some((title, content, tags))) => {
-						self.db.create_note(&Note::new(title, content, tags))?;
+				let msg = match open_editor_for_new_note(&self.config.editor, self.db.is_encrypted()) {
+					Ok(Some(parsed)) => {
+						// File the note in the notebook being shown, so it stays in view
+						let mut note = parsed.into_note();
+						note.notebook_id = self.notebook_filter.as_ref().map(|filter| filter.id);
+						self.db.create_note(&note)?;
 						self.refresh_notes()?;
 						"Note created"
 					}
@@ -87,24 +107,45 @@ impl App {
 				self.needs_clear = true;
 			}
 			KeyCode::Char(c) if c == self.config.keybindings.delete => {
-				if let Some(note) = self.get_selected_note()
-					&& let Some(id) = note.id
-				{
-					let title = &note.title;
-					self.db.delete_note(id)?;
-					self.set_message(format!("Deleted '{title}'"));
+				if let Some(note) = self.get_selected_note() {
+					let (id, title) = (note.id, note.title.clone());
+					self.db.trash_note(id)?;
+					self.set_message(format!("Moved '{title}' to trash"));
+					self.refresh_notes()?;
+				}
+			}
+			KeyCode::Char(c) if c == self.config.keybindings.pin => {
+				if let Some(note) = self.get_selected_note() {
+					let (id, title, pinned) = (note.id, note.title.clone(), !note.pinned);
+					self.db.set_pinned(id, pinned)?;
+					self.set_message(format!("{} '{title}'", if pinned { "Pinned" } else { "Unpinned" }));
+					self.refresh_notes()?;
+				}
+			}
+			KeyCode::Char(c) if c == self.config.keybindings.archive => {
+				if let Some(note) = self.get_selected_note() {
+					let (id, title, archived) = (note.id, note.title.clone(), !note.archived);
+					self.db.set_archived(id, archived)?;
+					self.set_message(format!("{} '{title}'", if archived { "Archived" } else { "Unarchived" }));
 					self.refresh_notes()?;
 				}
 			}
+			KeyCode::Char(c) if c == self.config.keybindings.trash => {
+				self.trash_view = true;
+				self.list_state.select(Some(0));
+				self.refresh_notes()?;
+			}
 			KeyCode::Char(c) if c == self.config.keybindings.sort => {
 				self.sort_mode = self.sort_mode.next();
 				self.refresh_notes()?;
 				self.set_message(format!("Sort: {}", self.sort_mode.name()));
 			}
 			KeyCode::Char(c) if c == self.config.keybindings.export => {
-				if let Some(note) = self.get_selected_note() {
+				if let Some(id) = self.get_selected_note().map(|n| n.id)
+					&& let Some(note) = self.db.get_note(id)?
+				{
 					let filename = format!("{}.md", sanitize_filename(&note.title));
-					let msg = match std::fs::write(&filename, note_to_markdown(note)) {
+					let msg = match std::fs::write(&filename, note_to_markdown(&note)) {
 						Ok(()) => format!("Exported to {filename}"),
 						Err(e) => format!("Export failed: {e}"),
 					};
@@ -112,40 +153,42 @@ impl App {
 				}
 			}
 			KeyCode::Char(c) if c == self.config.keybindings.edit || key == KeyCode::Enter => {
-				if let Some(note) = self.get_selected_note().cloned()
-					&& let Some(id) = note.id
+				if let Some(id) = self.get_selected_note().map(|n| n.id)
+					&& let Some(note) = self.db.get_note(id)?
 				{
-					match open_editor_for_edit(&note, &self.config.editor) {
-						Ok(Some((title, content, tags))) => {
-							self.db.update_note(id, &title, &content, &tags)?;
-							self.set_message("Note saved");
-							self.refresh_notes()?;
-						}
+					match open_editor_for_edit(&note, &self.config.editor, self.db.is_encrypted()) {
+						Ok(Some(parsed)) => self.save_edit(&note, parsed)?,
 						_ => self.set_message("Cancelled"),
 					}
 					self.needs_clear = true;
 				}
 			}
+			KeyCode::Char(c) if c == self.config.keybindings.tasks => self.open_tasks()?,
+			KeyCode::Char(c) if c == self.config.keybindings.tags => self.open_tag_tree()?,
+			KeyCode::Char(c) if c == self.config.keybindings.notebooks => self.open_notebook_tree()?,
 			KeyCode::Char(c) if c == self.config.keybindings.search => {
 				self.screen = Screen::SearchMode;
 				self.search.input_buffer = self.search.query.clone();
 			}
-			KeyCode::Char(c) if c == self.config.keybindings.goto_top => {
-				if !self.notes.is_empty() {
-					self.list_state.select(Some(0));
-					self.preview_scroll = 0;
-				}
+			KeyCode::Char(c) if c == self.config.keybindings.goto_top && !self.notes.is_empty() => {
+				self.list_state.select(Some(0));
+				self.preview_scroll = 0;
 			}
-			KeyCode::Char(c) if c == self.config.keybindings.goto_bottom => {
-				if !self.notes.is_empty() {
-					self.list_state.select(Some(self.notes.len() - 1));
-					self.preview_scroll = 0;
-				}
+			KeyCode::Char(c) if c == self.config.keybindings.goto_bottom && !self.notes.is_empty() => {
+				self.load_all_notes()?;
+				self.list_state.select(Some(self.notes.len() - 1));
+				self.preview_scroll = 0;
+			}
+			KeyCode::Down => self.navigate(true)?,
+			KeyCode::Up => self.navigate(false)?,
+			KeyCode::Char(c) if c == self.config.keybindings.move_down => self.navigate(true)?,
+			KeyCode::Char(c) if c == self.config.keybindings.move_up => self.navigate(false)?,
+			KeyCode::Esc if (self.tag_filter.is_some() || self.notebook_filter.is_some()) && !self.search.is_active() => {
+				self.tag_filter = None;
+				self.notebook_filter = None;
+				self.refresh_notes()?;
+				self.set_message("Filters cleared");
 			}
-			KeyCode::Down => self.navigate(true),
-			KeyCode::Up => self.navigate(false),
-			KeyCode::Char(c) if c == self.config.keybindings.move_down => self.navigate(true),
-			KeyCode::Char(c) if c == self.config.keybindings.move_up => self.navigate(false),
 			KeyCode::Esc => {
 				let (had_search, had_selections) = (self.search.is_active(), !self.selection.is_empty());
 				if had_search {
@@ -168,6 +211,28 @@ impl App {
 		Ok(false)
 	}
 
+	pub fn handle_confirm_input(&mut self, action: ConfirmAction, key: KeyCode) -> Result<bool> {
+		self.screen = Screen::List;
+		if !matches!(key, KeyCode::Char('y' | 'Y')) {
+			self.set_message("Cancelled");
+			return Ok(false);
+		}
+
+		let msg = match action {
+			ConfirmAction::TrashSelected => {
+				format!("Moved {} notes to trash", self.selection.trash_all(&self.db)?)
+			}
+			ConfirmAction::PurgeNote(id) => {
+				self.db.purge_note(id)?;
+				"Note deleted permanently".to_string()
+			}
+			ConfirmAction::EmptyTrash => format!("Permanently deleted {} notes", self.db.empty_trash()?),
+		};
+		self.set_message(msg);
+		self.refresh_notes()?;
+		Ok(false)
+	}
+
 	pub fn handle_search_input(&mut self, key: KeyCode, modifiers: KeyModifiers) -> Result<bool> {
 		if modifiers.contains(KeyModifiers::CONTROL)
 			&& let Some(down) = match key {
@@ -175,7 +240,7 @@ impl App {
 				KeyCode::Char('p' | 'k') | KeyCode::Up => Some(false),
 				_ => None,
 			} {
-			self.navigate(down);
+			self.navigate(down)?;
 			return Ok(false);
 		}
 
@@ -186,7 +251,9 @@ impl App {
 			}
 			KeyCode::Enter => {
 				self.screen = Screen::List;
-				if self.search.is_active() {
+				if let Some(err) = self.search.error.clone() {
+					self.set_message(err);
+				} else if self.search.is_active() {
 					self.set_message(format!("Found {} notes", self.notes.len()));
 				}
 			}
@@ -195,7 +262,7 @@ impl App {
 				self.search.set_query(self.search.input_buffer.clone());
 				self.refresh_notes()?;
 			}
-			KeyCode::Down | KeyCode::Up => self.navigate(matches!(key, KeyCode::Down)),
+			KeyCode::Down | KeyCode::Up => self.navigate(matches!(key, KeyCode::Down))?,
 			KeyCode::Char(c) => {
 				self.search.input_buffer.push(c);
 				self.search.set_query(self.search.input_buffer.clone());
@@ -205,4 +272,36 @@ impl App {
 		}
 		Ok(false)
 	}
+
+	/// Handles typing a tag to add to or remove from every selected note. The
+	/// selection is kept so further tags can be applied.
+	pub fn handle_tag_input(&mut self, add: bool, key: KeyCode) -> Result<bool> {
+		match key {
+			KeyCode::Esc => {
+				self.screen = Screen::List;
+				self.set_message("Cancelled");
+			}
+			KeyCode::Enter => {
+				self.screen = Screen::List;
+				let tag = normalize_tag(&self.tag_input);
+				if tag.is_empty() {
+					self.set_message("Cancelled");
+					return Ok(false);
+				}
+				let msg = if add {
+					format!("Tagged {} notes with '{tag}'", self.selection.tag_all(&self.db, &tag)?)
+				} else {
+					format!("Removed '{tag}' from {} notes", self.selection.untag_all(&self.db, &tag)?)
+				};
+				self.set_message(msg);
+				self.refresh_notes()?;
+			}
+			KeyCode::Backspace => {
+				self.tag_input.pop();
+			}
+			KeyCode::Char(c) => self.tag_input.push(c),
+			_ => {}
+		}
+		Ok(false)
+	}
 }
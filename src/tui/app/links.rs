@@ -0,0 +1,80 @@
+use anyhow::Result;
+
+use super::App;
+use crate::tui::markdown::wikilink_targets;
+
+impl App {
+	/// Index of the highlighted wikilink, if it belongs to the selected note.
+	pub fn active_link(&self) -> Option<usize> {
+		let (note_id, index) = self.link_cursor?;
+		(self.get_selected_note()?.id == note_id).then_some(index)
+	}
+
+	/// Moves the link cursor to the next or previous wikilink in the preview.
+	pub(super) fn cycle_link(&mut self, forward: bool) {
+		let Some((note_id, count)) =
+			self.preview_note().and_then(|note| Some((note.id?, wikilink_targets(&note.content, &self.config.theme).len())))
+		else {
+			return;
+		};
+		if count == 0 {
+			self.set_message("No links in this note");
+			return;
+		}
+
+		let index = match self.active_link() {
+			Some(i) if forward => (i + 1) % count,
+			Some(i) => (i + count - 1) % count,
+			None if forward => 0,
+			None => count - 1,
+		};
+		self.link_cursor = Some((note_id, index));
+	}
+
+	/// Selects the note behind the highlighted wikilink, clearing the search
+	/// or showing archived notes if that is needed to list it.
+	pub(super) fn follow_link(&mut self) -> Result<()> {
+		let Some(target) = self.active_link().and_then(|index| {
+			let note = self.preview_note()?;
+			wikilink_targets(&note.content, &self.config.theme).into_iter().nth(index)
+		}) else {
+			self.set_message("No link selected (Tab to pick one)");
+			return Ok(());
+		};
+
+		let Some(id) = self.db.resolve_link(&target)? else {
+			self.set_message(format!("No note titled '{target}'"));
+			return Ok(());
+		};
+
+		if self.reveal_note(id)? {
+			self.set_message(format!("Opened '{target}'"));
+		}
+		Ok(())
+	}
+
+	/// Selects a note in the list, clearing the search and tag filter or
+	/// showing archived notes if that is needed to list it. Returns false if it
+	/// cannot be listed.
+	pub(super) fn reveal_note(&mut self, id: i64) -> Result<bool> {
+		if !self.notes.iter().any(|n| n.id == id) {
+			if self.search.is_active() {
+				self.search.clear();
+			}
+			self.tag_filter = None;
+			if self.db.get_note(id)?.is_some_and(|n| n.archived) {
+				self.show_archived = true;
+			}
+			self.refresh_notes()?;
+		}
+		while !self.notes.iter().any(|n| n.id == id) && self.more_notes {
+			self.load_more_notes()?;
+		}
+
+		let Some(pos) = self.notes.iter().position(|n| n.id == id) else { return Ok(false) };
+		self.list_state.select(Some(pos));
+		self.preview_scroll = 0;
+		self.link_cursor = None;
+		Ok(true)
+	}
+}
@@ -1,53 +1,123 @@
+mod conflict;
 mod input;
+mod links;
 mod navigation;
+mod notebooks;
 mod search;
 mod selection;
 mod sorting;
+mod tags;
+mod tasks;
+mod trash;
+mod tree;
 
 use anyhow::Result;
+pub use conflict::PendingEdit;
+pub use notebooks::{NotebookFilter, NotebookTreeState};
+use qnote::db::{Attachment, Database, Note, NoteQuery, NoteSummary};
 use ratatui::widgets::ListState;
 pub use search::SearchState;
 pub use selection::SelectionState;
 pub use sorting::SortMode;
+pub use tags::TagTreeState;
+pub use tasks::TasksState;
+pub use tree::{TreeNode, TreeState};
 
-use crate::{config::Config, db::{Database, Note}};
+use crate::settings::Config;
 
-#[derive(PartialEq, Eq)]
+/// Notes loaded into the list at a time; more are loaded as the selection
+/// reaches the end.
+const LIST_PAGE_SIZE: usize = 200;
+
+#[derive(PartialEq, Eq, Clone, Copy)]
 pub enum Screen {
 	List,
 	SearchMode,
+	Confirm(ConfirmAction),
+	/// An edit clashed with a change made elsewhere; see `App::conflict`.
+	Conflict,
+	/// Tasks from every note; see `App::tasks`.
+	Tasks,
+	/// The tag hierarchy; see `App::tag_tree`.
+	Tags,
+	/// The notebook hierarchy; see `App::notebook_tree`.
+	Notebooks,
+	/// Typing a tag to add to (or remove from) every selected note; see
+	/// `App::tag_input`.
+	TagInput {
+		add: bool,
+	},
+}
+
+/// A destructive action waiting for a y/n answer.
+#[derive(PartialEq, Eq, Clone, Copy)]
+pub enum ConfirmAction {
+	TrashSelected,
+	PurgeNote(i64),
+	EmptyTrash,
 }
 
 pub struct App {
-	pub db:             Database,
-	pub config:         Config,
-	pub screen:         Screen,
-	pub notes:          Vec<Note>,
-	pub list_state:     ListState,
-	pub message:        Option<String>,
-	pub needs_clear:    bool,
-	pub preview_scroll: u16,
-	pub sort_mode:      SortMode,
-	pub help_expanded:  bool,
-	pub search:         SearchState,
-	pub selection:      SelectionState,
-	message_counter:    u8,
+	pub db:              Database,
+	pub config:          Config,
+	pub screen:          Screen,
+	/// Listed notes loaded so far; see `more_notes`.
+	pub notes:           Vec<NoteSummary>,
+	/// Number of notes in the list, loaded or not.
+	pub note_count:      usize,
+	/// Whether the list continues past `notes`, to be loaded by
+	/// `load_more_notes`.
+	more_notes:          bool,
+	/// Full text of the selected note, loaded by `sync_preview`.
+	pub preview:         Option<Note>,
+	/// Files attached to the previewed note, loaded along with it.
+	pub attachments:     Vec<Attachment>,
+	/// Notes linking to the previewed note, loaded along with it.
+	pub backlinks:       Vec<NoteSummary>,
+	pub list_state:      ListState,
+	pub message:         Option<String>,
+	pub needs_clear:     bool,
+	pub preview_scroll:  u16,
+	pub sort_mode:       SortMode,
+	pub help_expanded:   bool,
+	pub search:          SearchState,
+	pub selection:       SelectionState,
+	/// Whether the list shows the trash instead of live notes.
+	pub trash_view:      bool,
+	/// Whether archived notes are listed alongside the others.
+	pub show_archived:   bool,
+	/// Highlighted wikilink in the preview, as (note id, link index).
+	pub link_cursor:     Option<(i64, usize)>,
+	/// Edit waiting on a keep-mine/keep-theirs/copy decision.
+	pub conflict:        Option<PendingEdit>,
+	pub tasks:           TasksState,
+	pub tag_tree:        TagTreeState,
+	/// Only notes with this tag (or one nested below it) are listed.
+	pub tag_filter:      Option<String>,
+	pub notebook_tree:   NotebookTreeState,
+	/// Only notes in this notebook (or one below it) are listed.
+	pub notebook_filter: Option<NotebookFilter>,
+	/// Tag being typed on the `TagInput` screen.
+	pub tag_input:       String,
+	/// Last seen `PRAGMA data_version`, to notice other processes' writes.
+	data_version:        i64,
+	message_counter:     u8,
 }
 
 impl App {
 	pub fn new(db: Database, config: Config) -> Result<Self> {
-		let notes = db.list_notes()?;
-		let mut list_state = ListState::default();
-		if !notes.is_empty() {
-			list_state.select(Some(0));
-		}
-
-		Ok(Self {
+		let data_version = db.data_version()?;
+		let mut app = Self {
 			db,
 			config,
 			screen: Screen::List,
-			notes,
-			list_state,
+			notes: Vec::new(),
+			note_count: 0,
+			more_notes: false,
+			preview: None,
+			attachments: Vec::new(),
+			backlinks: Vec::new(),
+			list_state: ListState::default(),
 			message: None,
 			message_counter: 0,
 			needs_clear: false,
@@ -56,7 +126,20 @@ impl App {
 			help_expanded: false,
 			search: SearchState::default(),
 			selection: SelectionState::default(),
-		})
+			trash_view: false,
+			show_archived: false,
+			link_cursor: None,
+			conflict: None,
+			tasks: TasksState::default(),
+			tag_tree: TagTreeState::default(),
+			tag_filter: None,
+			notebook_tree: NotebookTreeState::default(),
+			notebook_filter: None,
+			tag_input: String::new(),
+			data_version,
+		};
+		app.refresh_notes()?;
+		Ok(app)
 	}
 
 	pub fn set_message(&mut self, msg: impl Into<String>) {
@@ -71,21 +154,120 @@ impl App {
 		}
 	}
 
-	pub fn get_selected_note(&self) -> Option<&Note> { self.list_state.selected().and_then(|i| self.notes.get(i)) }
+	pub fn get_selected_note(&self) -> Option<&NoteSummary> { self.list_state.selected().and_then(|i| self.notes.get(i)) }
+
+	/// Returns the full selected note, if `sync_preview` has loaded it.
+	pub fn preview_note(&self) -> Option<&Note> {
+		let selected = self.get_selected_note()?;
+		self.preview.as_ref().filter(|note| note.id == Some(selected.id))
+	}
+
+	/// Loads the content, attachments and backlinks of the selected note when
+	/// the selection changes. Only this note is read in full; the list holds
+	/// summaries.
+	pub fn sync_preview(&mut self) -> Result<()> {
+		if self.preview_note().is_none() {
+			self.preview = match self.get_selected_note() {
+				Some(selected) => self.db.get_note(selected.id)?,
+				None => None,
+			};
+			let id = self.preview.as_ref().and_then(|note| note.id);
+			self.attachments = self.load_for_preview(id, "attachments", Database::list_attachments);
+			self.backlinks = self.load_for_preview(id, "backlinks", Database::backlinks);
+		}
+		Ok(())
+	}
+
+	/// Loads something shown below the preview, reporting a failure in the
+	/// status line rather than leaving the section silently empty.
+	fn load_for_preview<T>(&mut self, id: Option<i64>, what: &str, load: fn(&Database, i64) -> Result<Vec<T>>) -> Vec<T> {
+		match id.map(|id| load(&self.db, id)) {
+			Some(Ok(items)) => items,
+			Some(Err(e)) => {
+				self.set_message(format!("Could not load {what}: {e}"));
+				Vec::new()
+			}
+			None => Vec::new(),
+		}
+	}
 
 	pub fn is_note_selected(&self, note_id: i64) -> bool { self.selection.contains(note_id) }
 
+	/// The notes listed outside the trash, before any search.
+	fn listing(&self) -> NoteQuery {
+		self
+			.sort_mode
+			.query()
+			.include_archived(self.show_archived)
+			.tags_any(self.tag_filter.clone())
+			.notebook(self.notebook_filter.as_ref().map(|filter| filter.id))
+	}
+
+	/// Reloads the list. Outside a search, only pages up to the selected note
+	/// are loaded.
 	fn refresh_notes(&mut self) -> Result<()> {
-		self.notes = self.search.refresh_notes(&self.db, self.sort_mode, &mut self.list_state, &mut self.preview_scroll)?;
+		self.preview = None;
+		if self.trash_view {
+			self.notes = self.db.list_trash()?;
+			self.more_notes = false;
+			selection::clamp_selection(&mut self.list_state, self.notes.len(), &mut self.preview_scroll);
+		} else {
+			let listing = self.listing();
+			let paged = !self.search.is_active();
+			let limit = self.list_state.selected().map_or(0, |i| i + 1).max(LIST_PAGE_SIZE);
+			let query = listing.clone().limit(paged.then_some(limit));
+			let Some(notes) = self.search.refresh_notes(&self.db, &query, &mut self.list_state, &mut self.preview_scroll)?
+			else {
+				return Ok(());
+			};
+			self.notes = notes;
+			self.more_notes = paged && self.notes.len() == limit;
+			if self.more_notes {
+				self.note_count = self.db.count_notes(&listing)?;
+				return Ok(());
+			}
+		}
+		self.note_count = self.notes.len();
+		Ok(())
+	}
+
+	/// Loads the next page of the list, if there is one.
+	fn load_more_notes(&mut self) -> Result<()> {
+		if self.more_notes {
+			let page = self.db.query_notes(&self.listing().limit(Some(LIST_PAGE_SIZE)).offset(self.notes.len()))?;
+			self.more_notes = page.len() == LIST_PAGE_SIZE;
+			self.notes.extend(page);
+		}
+		Ok(())
+	}
+
+	/// Loads the rest of the list, for actions on all of it.
+	fn load_all_notes(&mut self) -> Result<()> {
+		if self.more_notes {
+			let rest = self.db.query_notes(&self.listing().offset(self.notes.len()))?;
+			self.notes.extend(rest);
+			self.more_notes = false;
+			self.note_count = self.notes.len();
+		}
 		Ok(())
 	}
 
-	fn navigate(&mut self, down: bool) {
+	/// Asks for confirmation before running `action`.
+	fn request_confirm(&mut self, action: ConfirmAction, prompt: impl Into<String>) {
+		self.screen = Screen::Confirm(action);
+		self.set_message(format!("{} (y/N)", prompt.into()));
+	}
+
+	fn navigate(&mut self, down: bool) -> Result<()> {
+		if down && self.list_state.selected().is_some_and(|i| i + 1 >= self.notes.len()) {
+			self.load_more_notes()?;
+		}
 		selection::navigate_list(&mut self.list_state, &self.notes, &mut self.preview_scroll, down);
+		Ok(())
 	}
 
 	fn scroll_preview(&mut self, down: bool) {
-		if let Some(note) = self.get_selected_note() {
+		if let Some(note) = self.preview_note() {
 			let content_height = navigation::get_preview_content_height(note, &self.config.ui);
 			navigation::scroll_preview(&mut self.preview_scroll, down, content_height, &self.config.ui);
 		}
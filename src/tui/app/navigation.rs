@@ -1,4 +1,6 @@
-use crate::{config::UiConfig, db::Note};
+use qnote::db::Note;
+
+use crate::settings::UiConfig;
 
 /// Estimates the height of the preview content for scroll bounds checking.
 pub fn get_preview_content_height(note: &Note, ui_config: &UiConfig) -> u16 {
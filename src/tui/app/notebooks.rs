@@ -0,0 +1,59 @@
+use anyhow::Result;
+use qnote::db::NotebookNode;
+use ratatui::crossterm::event::{KeyCode, KeyModifiers};
+
+use super::{App, Screen, tree::TreeState};
+
+/// The notebook hierarchy shown in the notebook tree.
+pub type NotebookTreeState = TreeState<NotebookNode>;
+
+/// Notebook the note list is limited to.
+#[derive(Clone)]
+pub struct NotebookFilter {
+	pub id:   i64,
+	pub path: String,
+}
+
+impl App {
+	/// Switches to the notebook tree, with the current notebook filter
+	/// selected.
+	pub(super) fn open_notebook_tree(&mut self) -> Result<()> {
+		self.notebook_tree.roots = self.db.notebook_tree()?;
+		if self.notebook_tree.roots.is_empty() {
+			self.set_message("No notebooks yet (create one with `qnote notebook create`)");
+			return Ok(());
+		}
+
+		self.notebook_tree.reveal(self.notebook_filter.as_ref().map(|filter| filter.path.as_str()));
+		self.screen = Screen::Notebooks;
+		Ok(())
+	}
+
+	/// Handles keys while the notebook tree is open.
+	pub fn handle_notebook_tree_input(&mut self, key: KeyCode, modifiers: KeyModifiers) -> Result<bool> {
+		if modifiers.contains(KeyModifiers::CONTROL) {
+			return Ok(key == KeyCode::Char('c'));
+		}
+
+		let kb = &self.config.keybindings;
+		match key {
+			KeyCode::Char(c) if c == kb.quit => return Ok(true),
+			KeyCode::Char(c) if c == kb.notebooks => self.screen = Screen::List,
+			KeyCode::Esc => self.screen = Screen::List,
+			KeyCode::Enter => {
+				if let Some((_, node)) = self.notebook_tree.selected() {
+					let filter = NotebookFilter { id: node.id, path: node.path.clone() };
+					self.set_message(format!("Showing notes in '{}' (ESC to clear)", filter.path));
+					self.notebook_filter = Some(filter);
+					self.screen = Screen::List;
+					self.list_state.select(Some(0));
+					self.refresh_notes()?;
+				}
+			}
+			_ => {
+				self.notebook_tree.handle_key(key, kb);
+			}
+		}
+		Ok(false)
+	}
+}
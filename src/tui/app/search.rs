@@ -1,16 +1,16 @@
 use anyhow::Result;
-use fuzzy_matcher::{FuzzyMatcher, skim::SkimMatcherV2};
+use qnote::db::{Database, NoteQuery, NoteSummary, QueryError};
 use ratatui::widgets::ListState;
 
-use super::sorting::SortMode;
-use crate::db::{Database, Note};
+use super::selection::clamp_selection;
 
 #[derive(Default)]
 pub struct SearchState {
 	pub query:         String,
 	pub input_buffer:  String,
 	pub match_indices: Vec<Vec<usize>>,
-	matcher:           SkimMatcherV2,
+	/// Why the current query could not be run (e.g. an unclosed quote).
+	pub error:         Option<String>,
 }
 
 impl SearchState {
@@ -20,6 +20,7 @@ impl SearchState {
 		self.query.clear();
 		self.input_buffer.clear();
 		self.match_indices.clear();
+		self.error = None;
 	}
 
 	pub fn set_query(&mut self, query: String) {
@@ -27,44 +28,41 @@ impl SearchState {
 		self.input_buffer = query;
 	}
 
+	/// Reloads notes for the current query, within the notes listed by
+	/// `listing` (whose order is used when there is no query). Returns `None`
+	/// when the query is invalid, in which case the previous results should
+	/// stay on screen.
 	pub fn refresh_notes(
 		&mut self,
 		db: &Database,
-		sort_mode: SortMode,
+		listing: &NoteQuery,
 		list_state: &mut ListState,
 		preview_scroll: &mut u16,
-	) -> Result<Vec<Note>> {
-		let current_index = list_state.selected();
-		let all_notes = db.list_notes()?;
-
+	) -> Result<Option<Vec<NoteSummary>>> {
 		let notes = if self.query.is_empty() {
 			self.match_indices.clear();
-			let mut notes = all_notes;
-			sort_mode.sort_notes(&mut notes);
-			notes
+			self.error = None;
+			db.query_notes(listing)?
 		} else {
-			let mut scored: Vec<_> = all_notes
-				.into_iter()
-				.filter_map(|note| {
-					let text = format!("{} {}", note.title, note.content);
-					self.matcher.fuzzy_indices(&text, &self.query).map(|(score, indices)| (note, score, indices))
-				})
-				.collect();
-
-			scored.sort_unstable_by(|a, b| b.1.cmp(&a.1));
-			let (notes, indices): (Vec<_>, Vec<_>) = scored.into_iter().map(|(note, _, indices)| (note, indices)).unzip();
-
-			self.match_indices = indices;
-			notes
+			match db.search_notes(&self.query, listing) {
+				Ok(results) => {
+					self.error = None;
+					let (notes, indices): (Vec<_>, Vec<_>) =
+						results.into_iter().map(|result| (result.note, result.title_matches)).unzip();
+					self.match_indices = indices;
+					notes
+				}
+				Err(e) => match e.downcast_ref::<QueryError>() {
+					Some(query_error) => {
+						self.error = Some(query_error.to_string());
+						return Ok(None);
+					}
+					None => return Err(e),
+				},
+			}
 		};
 
-		list_state.select(if notes.is_empty() {
-			None
-		} else {
-			Some(current_index.map_or(0, |idx| idx.min(notes.len() - 1)))
-		});
-
-		*preview_scroll = 0;
-		Ok(notes)
+		clamp_selection(list_state, notes.len(), preview_scroll);
+		Ok(Some(notes))
 	}
 }
@@ -1,9 +1,9 @@
-use std::collections::HashSet;
+use std::{collections::HashSet, io::Write};
 
-use anyhow::Result;
+use anyhow::{Context, Result};
+use qnote::{db::{Database, NoteSummary}, utils::{note_to_markdown, sanitize_filename}};
 use ratatui::widgets::ListState;
-
-use crate::{db::{Database, Note}, utils::{note_to_markdown, sanitize_filename}};
+use tempfile::NamedTempFile;
 
 #[derive(Default)]
 pub struct SelectionState {
@@ -19,8 +19,8 @@ impl SelectionState {
 		}
 	}
 
-	pub fn select_all(&mut self, notes: &[Note]) -> usize {
-		self.selected_notes.extend(notes.iter().filter_map(|n| n.id));
+	pub fn select_all(&mut self, notes: &[NoteSummary]) -> usize {
+		self.selected_notes.extend(notes.iter().map(|n| n.id));
 		self.selected_notes.len()
 	}
 
@@ -34,45 +34,60 @@ impl SelectionState {
 
 	pub fn len(&self) -> usize { self.selected_notes.len() }
 
-	pub fn delete_all(&mut self, db: &Database) -> Result<usize> {
-		let count = self.len();
-		for note_id in self.selected_notes.drain() {
-			db.delete_note(note_id)?;
-		}
+	fn ids(&self) -> Vec<i64> { self.selected_notes.iter().copied().collect() }
+
+	/// Moves every selected note to the trash in one transaction. The
+	/// selection is kept if that fails.
+	pub fn trash_all(&mut self, db: &Database) -> Result<usize> {
+		let ids: Vec<i64> = self.selected_notes.iter().copied().collect();
+		let count = db.trash_notes(&ids)?;
+		self.selected_notes.clear();
 		Ok(count)
 	}
 
-	pub fn export_all(&mut self, notes: &[Note]) -> (usize, usize) {
-		let (success, errors) =
-			notes.iter().filter(|n| n.id.is_some_and(|id| self.selected_notes.contains(&id))).fold((0, 0), |(s, e), note| {
-				let filename = format!("{}.md", sanitize_filename(&note.title));
-				match std::fs::write(&filename, note_to_markdown(note)) {
-					Ok(()) => (s + 1, e),
-					Err(_) => (s, e + 1),
-				}
-			});
+	/// Adds a tag to every selected note in one transaction. Returns the
+	/// number of notes that did not have it yet.
+	pub fn tag_all(&self, db: &Database, tag: &str) -> Result<usize> { db.add_tag_to_notes(&self.ids(), tag) }
 
-		self.selected_notes.clear();
-		(success, errors)
-	}
-}
+	/// Removes a tag from every selected note in one transaction. Returns the
+	/// number of notes that had it.
+	pub fn untag_all(&self, db: &Database, tag: &str) -> Result<usize> { db.remove_tag_from_notes(&self.ids(), tag) }
 
-pub fn toggle_and_navigate(
-	selection: &mut SelectionState,
-	list_state: &mut ListState,
-	notes: &[Note],
-	preview_scroll: &mut u16,
-) {
-	if let Some(i) = list_state.selected()
-		&& let Some(note) = notes.get(i)
-		&& let Some(id) = note.id
-	{
-		selection.toggle(id);
-		navigate_list(list_state, notes, preview_scroll, true);
+	/// Writes each selected note to `<title>.md`, adding the note ID to titles
+	/// that would share a file. All or nothing: every note is written to a
+	/// temporary file first and moved into place only once all of them are
+	/// written, so a note that cannot be written leaves the directory as it
+	/// was and keeps the selection.
+	pub fn export_all(&mut self, db: &Database) -> Result<usize> {
+		let mut ids: Vec<i64> = self.selected_notes.iter().copied().collect();
+		ids.sort_unstable();
+		let notes = db.get_notes(&ids)?;
+
+		let mut taken = HashSet::new();
+		let mut staged = Vec::with_capacity(notes.len());
+		for note in &notes {
+			let name = sanitize_filename(&note.title);
+			let mut filename = format!("{name}.md");
+			// Compare case-insensitively, as some filesystems do
+			if !taken.insert(filename.to_lowercase()) {
+				filename = format!("{name}-{}.md", note.id.unwrap_or_default());
+				taken.insert(filename.to_lowercase());
+			}
+			let mut file = NamedTempFile::new_in(".").with_context(|| format!("Could not write {filename}"))?;
+			file.write_all(note_to_markdown(note).as_bytes()).with_context(|| format!("Could not write {filename}"))?;
+			staged.push((file, filename));
+		}
+
+		let count = staged.len();
+		for (file, filename) in staged {
+			file.persist(&filename).with_context(|| format!("Could not write {filename}"))?;
+		}
+		self.selected_notes.clear();
+		Ok(count)
 	}
 }
 
-pub fn navigate_list(list_state: &mut ListState, notes: &[Note], preview_scroll: &mut u16, down: bool) {
+pub fn navigate_list(list_state: &mut ListState, notes: &[NoteSummary], preview_scroll: &mut u16, down: bool) {
 	if !notes.is_empty() {
 		let current = list_state.selected().unwrap_or(0);
 		let new_index = if down { (current + 1).min(notes.len() - 1) } else { current.saturating_sub(1) };
@@ -80,3 +95,10 @@ pub fn navigate_list(list_state: &mut ListState, notes: &[Note], preview_scroll:
 		*preview_scroll = 0;
 	}
 }
+
+/// Keeps the selection within bounds after the list changes.
+pub fn clamp_selection(list_state: &mut ListState, len: usize, preview_scroll: &mut u16) {
+	let current_index = list_state.selected();
+	list_state.select(if len == 0 { None } else { Some(current_index.map_or(0, |idx| idx.min(len - 1))) });
+	*preview_scroll = 0;
+}
@@ -1,4 +1,4 @@
-use crate::db::Note;
+use qnote::db::{NoteQuery, SortKey};
 
 /// Note sorting mode (cycle with 's' key).
 #[derive(PartialEq, Eq, Clone, Copy)]
@@ -36,15 +36,16 @@ impl SortMode {
 		}
 	}
 
-	/// Sorts notes in-place according to this mode.
-	pub fn sort_notes(self, notes: &mut [Note]) {
-		notes.sort_unstable_by(|a, b| match self {
-			Self::UpdatedDesc => b.updated_at.cmp(&a.updated_at),
-			Self::UpdatedAsc => a.updated_at.cmp(&b.updated_at),
-			Self::TitleAsc => a.title.to_lowercase().cmp(&b.title.to_lowercase()),
-			Self::TitleDesc => b.title.to_lowercase().cmp(&a.title.to_lowercase()),
-			Self::CreatedDesc => b.created_at.cmp(&a.created_at),
-			Self::CreatedAsc => a.created_at.cmp(&b.created_at),
-		});
+	/// Returns a query listing notes in this order (pinned notes first).
+	pub fn query(self) -> NoteQuery {
+		let (key, descending) = match self {
+			Self::UpdatedDesc => (SortKey::Updated, true),
+			Self::UpdatedAsc => (SortKey::Updated, false),
+			Self::TitleAsc => (SortKey::Title, false),
+			Self::TitleDesc => (SortKey::Title, true),
+			Self::CreatedDesc => (SortKey::Created, true),
+			Self::CreatedAsc => (SortKey::Created, false),
+		};
+		NoteQuery::new().sort(key, descending)
 	}
 }
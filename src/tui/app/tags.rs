@@ -0,0 +1,51 @@
+use anyhow::Result;
+use qnote::db::TagNode;
+use ratatui::crossterm::event::{KeyCode, KeyModifiers};
+
+use super::{App, Screen, tree::TreeState};
+
+/// The tag hierarchy shown in the tag tree.
+pub type TagTreeState = TreeState<TagNode>;
+
+impl App {
+	/// Switches to the tag tree, with the current tag filter selected.
+	pub(super) fn open_tag_tree(&mut self) -> Result<()> {
+		self.tag_tree.roots = self.db.tag_tree()?;
+		if self.tag_tree.roots.is_empty() {
+			self.set_message("No tags yet");
+			return Ok(());
+		}
+
+		self.tag_tree.reveal(self.tag_filter.as_deref());
+		self.screen = Screen::Tags;
+		Ok(())
+	}
+
+	/// Handles keys while the tag tree is open.
+	pub fn handle_tag_tree_input(&mut self, key: KeyCode, modifiers: KeyModifiers) -> Result<bool> {
+		if modifiers.contains(KeyModifiers::CONTROL) {
+			return Ok(key == KeyCode::Char('c'));
+		}
+
+		let kb = &self.config.keybindings;
+		match key {
+			KeyCode::Char(c) if c == kb.quit => return Ok(true),
+			KeyCode::Char(c) if c == kb.tags => self.screen = Screen::List,
+			KeyCode::Esc => self.screen = Screen::List,
+			KeyCode::Enter => {
+				if let Some((_, node)) = self.tag_tree.selected() {
+					let path = node.path.clone();
+					self.set_message(format!("Showing notes tagged '{path}' (ESC to clear)"));
+					self.tag_filter = Some(path);
+					self.screen = Screen::List;
+					self.list_state.select(Some(0));
+					self.refresh_notes()?;
+				}
+			}
+			_ => {
+				self.tag_tree.handle_key(key, kb);
+			}
+		}
+		Ok(false)
+	}
+}
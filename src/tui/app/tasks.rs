@@ -0,0 +1,100 @@
+use anyhow::Result;
+use qnote::db::{NoteQuery, Task};
+use ratatui::{crossterm::event::{KeyCode, KeyModifiers}, widgets::ListState};
+
+use super::{App, Screen};
+
+/// Tasks listed in the tasks view.
+#[derive(Default)]
+pub struct TasksState {
+	pub items:      Vec<Task>,
+	pub list_state: ListState,
+	/// Whether checked tasks are listed too.
+	pub show_done:  bool,
+}
+
+impl TasksState {
+	pub fn selected(&self) -> Option<&Task> { self.list_state.selected().and_then(|i| self.items.get(i)) }
+
+	fn navigate(&mut self, down: bool) {
+		if !self.items.is_empty() {
+			let current = self.list_state.selected().unwrap_or(0);
+			let index = if down { (current + 1).min(self.items.len() - 1) } else { current.saturating_sub(1) };
+			self.list_state.select(Some(index));
+		}
+	}
+}
+
+impl App {
+	/// Switches to the tasks view.
+	pub(super) fn open_tasks(&mut self) -> Result<()> {
+		self.screen = Screen::Tasks;
+		self.tasks.list_state.select(Some(0));
+		self.refresh_tasks()
+	}
+
+	/// Reloads tasks from every listed note (within the tag filter), keeping
+	/// the selection in bounds.
+	pub(super) fn refresh_tasks(&mut self) -> Result<()> {
+		let query = NoteQuery::new().include_archived(self.show_archived).tags_any(self.tag_filter.clone());
+		self.tasks.items = self.db.tasks(&query, !self.tasks.show_done)?;
+		let len = self.tasks.items.len();
+		let current = self.tasks.list_state.selected();
+		self.tasks.list_state.select(if len == 0 { None } else { Some(current.map_or(0, |i| i.min(len - 1))) });
+		Ok(())
+	}
+
+	/// Handles keys while the tasks view is open.
+	pub fn handle_tasks_input(&mut self, key: KeyCode, modifiers: KeyModifiers) -> Result<bool> {
+		if modifiers.contains(KeyModifiers::CONTROL) {
+			return Ok(key == KeyCode::Char('c'));
+		}
+
+		let kb = &self.config.keybindings;
+		match key {
+			KeyCode::Char(c) if c == kb.quit => return Ok(true),
+			KeyCode::Char(c) if c == kb.tasks => self.screen = Screen::List,
+			KeyCode::Esc => self.screen = Screen::List,
+			KeyCode::Char(' ' | 'x') => self.toggle_task()?,
+			KeyCode::Char('a') => {
+				self.tasks.show_done = !self.tasks.show_done;
+				self.refresh_tasks()?;
+				self.set_message(if self.tasks.show_done { "Showing done tasks" } else { "Hiding done tasks" });
+			}
+			KeyCode::Enter => {
+				if let Some(task) = self.tasks.selected() {
+					let (id, title) = (task.note_id, task.note_title.clone());
+					self.screen = Screen::List;
+					if self.reveal_note(id)? {
+						self.set_message(format!("Opened '{title}'"));
+					}
+				}
+			}
+			KeyCode::Char(c) if c == kb.goto_top && !self.tasks.items.is_empty() => self.tasks.list_state.select(Some(0)),
+			KeyCode::Char(c) if c == kb.goto_bottom && !self.tasks.items.is_empty() => {
+				self.tasks.list_state.select(Some(self.tasks.items.len() - 1));
+			}
+			KeyCode::Down => self.tasks.navigate(true),
+			KeyCode::Up => self.tasks.navigate(false),
+			KeyCode::Char(c) if c == kb.move_down => self.tasks.navigate(true),
+			KeyCode::Char(c) if c == kb.move_up => self.tasks.navigate(false),
+			_ => {}
+		}
+		Ok(false)
+	}
+
+	/// Checks or unchecks the selected task in its note.
+	fn toggle_task(&mut self) -> Result<()> {
+		let Some(task) = self.tasks.selected() else { return Ok(()) };
+		let (note_id, line, done) = (task.note_id, task.line, !task.done);
+
+		let msg = match self.db.set_task_done(note_id, line, done) {
+			Ok(_) if done => "Task done".to_string(),
+			Ok(_) => "Task reopened".to_string(),
+			Err(e) => format!("Could not update task: {e}"),
+		};
+		self.set_message(msg);
+		self.refresh_tasks()?;
+		self.refresh_notes()
+	}
+}
@@ -0,0 +1,69 @@
+use anyhow::Result;
+use ratatui::crossterm::event::{KeyCode, KeyModifiers};
+
+use super::{App, ConfirmAction};
+
+impl App {
+	/// Handles keys while the list shows the trash.
+	pub fn handle_trash_input(&mut self, key: KeyCode, modifiers: KeyModifiers) -> Result<bool> {
+		if modifiers.contains(KeyModifiers::CONTROL) {
+			match key {
+				KeyCode::Char('c') => return Ok(true),
+				KeyCode::Char('j') => self.scroll_preview(true),
+				KeyCode::Char('k') => self.scroll_preview(false),
+				_ => {}
+			}
+			return Ok(false);
+		}
+
+		let kb = &self.config.keybindings;
+		match key {
+			KeyCode::Char('D') => {
+				if self.notes.is_empty() {
+					self.set_message("Trash is empty");
+				} else {
+					let count = self.notes.len();
+					self.request_confirm(ConfirmAction::EmptyTrash, format!("Permanently delete {count} notes?"));
+				}
+			}
+			KeyCode::Char('.') => self.help_expanded = !self.help_expanded,
+			KeyCode::Char(c) if c == kb.quit => return Ok(true),
+			KeyCode::Char(c) if c == kb.trash => self.leave_trash()?,
+			KeyCode::Esc => self.leave_trash()?,
+			KeyCode::Char(c) if c == kb.restore => {
+				if let Some(note) = self.get_selected_note() {
+					let (id, title) = (note.id, note.title.clone());
+					self.db.restore_note(id)?;
+					self.set_message(format!("Restored '{title}'"));
+					self.refresh_notes()?;
+				}
+			}
+			KeyCode::Char(c) if c == kb.delete => {
+				if let Some(note) = self.get_selected_note() {
+					let (id, prompt) = (note.id, format!("Permanently delete '{}'?", note.title));
+					self.request_confirm(ConfirmAction::PurgeNote(id), prompt);
+				}
+			}
+			KeyCode::Char(c) if c == kb.goto_top && !self.notes.is_empty() => {
+				self.list_state.select(Some(0));
+				self.preview_scroll = 0;
+			}
+			KeyCode::Char(c) if c == kb.goto_bottom && !self.notes.is_empty() => {
+				self.list_state.select(Some(self.notes.len() - 1));
+				self.preview_scroll = 0;
+			}
+			KeyCode::Down => self.navigate(true)?,
+			KeyCode::Up => self.navigate(false)?,
+			KeyCode::Char(c) if c == kb.move_down => self.navigate(true)?,
+			KeyCode::Char(c) if c == kb.move_up => self.navigate(false)?,
+			_ => {}
+		}
+		Ok(false)
+	}
+
+	fn leave_trash(&mut self) -> Result<()> {
+		self.trash_view = false;
+		self.list_state.select(Some(0));
+		self.refresh_notes()
+	}
+}
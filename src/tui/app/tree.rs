@@ -0,0 +1,139 @@
+use std::collections::HashSet;
+
+use qnote::db::{NotebookNode, TagNode};
+use ratatui::{crossterm::event::KeyCode, widgets::ListState};
+
+use crate::settings::KeybindingsConfig;
+
+/// A node of a hierarchy shown in a collapsible tree, identified by its
+/// `/`-separated path.
+pub trait TreeNode: Sized {
+	fn name(&self) -> &str;
+	fn path(&self) -> &str;
+	/// Notes in this node or any node below it.
+	fn count(&self) -> usize;
+	fn children(&self) -> &[Self];
+}
+
+impl TreeNode for TagNode {
+	fn name(&self) -> &str { &self.name }
+
+	fn path(&self) -> &str { &self.path }
+
+	fn count(&self) -> usize { self.count }
+
+	fn children(&self) -> &[Self] { &self.children }
+}
+
+impl TreeNode for NotebookNode {
+	fn name(&self) -> &str { &self.name }
+
+	fn path(&self) -> &str { &self.path }
+
+	fn count(&self) -> usize { self.count }
+
+	fn children(&self) -> &[Self] { &self.children }
+}
+
+/// A hierarchy shown as a tree, with its collapsed state.
+pub struct TreeState<N> {
+	pub roots:      Vec<N>,
+	/// Paths of the nodes whose children are shown; every node starts
+	/// collapsed.
+	pub expanded:   HashSet<String>,
+	pub list_state: ListState,
+}
+
+impl<N> Default for TreeState<N> {
+	fn default() -> Self { Self { roots: Vec::new(), expanded: HashSet::new(), list_state: ListState::default() } }
+}
+
+impl<N: TreeNode> TreeState<N> {
+	/// Returns the nodes currently on screen with their depth, in order.
+	pub fn visible(&self) -> Vec<(usize, &N)> {
+		fn walk<'a, N: TreeNode>(nodes: &'a [N], depth: usize, expanded: &HashSet<String>, out: &mut Vec<(usize, &'a N)>) {
+			for node in nodes {
+				out.push((depth, node));
+				if expanded.contains(node.path()) {
+					walk(node.children(), depth + 1, expanded, out);
+				}
+			}
+		}
+
+		let mut out = Vec::new();
+		walk(&self.roots, 0, &self.expanded, &mut out);
+		out
+	}
+
+	/// Number of nodes in the whole tree, collapsed or not.
+	pub fn total(&self) -> usize {
+		fn count<N: TreeNode>(nodes: &[N]) -> usize { nodes.iter().map(|node| 1 + count(node.children())).sum() }
+		count(&self.roots)
+	}
+
+	pub(super) fn selected(&self) -> Option<(usize, &N)> {
+		self.list_state.selected().and_then(|i| self.visible().get(i).copied())
+	}
+
+	/// Expands the ancestors of `path` so it is on screen, and selects it (or
+	/// the first node if None or not found).
+	pub(super) fn reveal(&mut self, path: Option<&str>) {
+		if let Some(path) = path {
+			let mut end = 0;
+			for segment in path.split('/') {
+				end += segment.len();
+				self.expanded.insert(path[..end].to_string());
+				end += 1;
+			}
+			self.expanded.remove(path);
+		}
+		let index = self.visible().iter().position(|(_, node)| Some(node.path()) == path);
+		self.list_state.select(Some(index.unwrap_or(0)));
+	}
+
+	/// Handles the keys shared by every tree: moving, and collapsing or
+	/// expanding nodes. Returns false for any other key.
+	pub(super) fn handle_key(&mut self, key: KeyCode, kb: &KeybindingsConfig) -> bool {
+		match key {
+			KeyCode::Right | KeyCode::Char('l') => self.set_expanded(true),
+			KeyCode::Left | KeyCode::Char('h') => self.set_expanded(false),
+			KeyCode::Char(' ') | KeyCode::Tab => {
+				let expanded = self.selected().is_some_and(|(_, node)| self.expanded.contains(node.path()));
+				self.set_expanded(!expanded);
+			}
+			KeyCode::Char(c) if c == kb.goto_top => self.list_state.select(Some(0)),
+			KeyCode::Char(c) if c == kb.goto_bottom => {
+				let len = self.visible().len();
+				self.list_state.select(len.checked_sub(1));
+			}
+			KeyCode::Down => self.navigate(true),
+			KeyCode::Up => self.navigate(false),
+			KeyCode::Char(c) if c == kb.move_down => self.navigate(true),
+			KeyCode::Char(c) if c == kb.move_up => self.navigate(false),
+			_ => return false,
+		}
+		true
+	}
+
+	fn navigate(&mut self, down: bool) {
+		let len = self.visible().len();
+		if len > 0 {
+			let current = self.list_state.selected().unwrap_or(0);
+			self.list_state.select(Some(if down { (current + 1).min(len - 1) } else { current.saturating_sub(1) }));
+		}
+	}
+
+	/// Shows or hides the children of the selected node. Collapsing a node
+	/// that is already collapsed moves to its parent.
+	fn set_expanded(&mut self, expand: bool) {
+		let Some((depth, node)) = self.selected() else { return };
+		let (path, has_children) = (node.path().to_string(), !node.children().is_empty());
+		if expand && has_children {
+			self.expanded.insert(path);
+		} else if !expand && !self.expanded.remove(&path) && depth > 0 {
+			let parent = path.rsplit_once('/').map(|(parent, _)| parent);
+			let index = self.visible().iter().position(|(_, node)| Some(node.path()) == parent);
+			self.list_state.select(index);
+		}
+	}
+}
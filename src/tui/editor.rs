@@ -1,9 +1,11 @@
-use std::{env, fs, io::{self, Write}, path::PathBuf, process::Command};
+use std::{env, fs, io::{self, Write}, process::Command};
 
 use anyhow::{Context, Result};
+use qnote::{db::Note, utils::{ParsedNote, parse_markdown_file, properties_to_frontmatter, tags_to_frontmatter}};
 use ratatui::crossterm::{execute, terminal::{EnterAlternateScreen, LeaveAlternateScreen, disable_raw_mode, enable_raw_mode}};
+use tempfile::NamedTempFile;
 
-use crate::{config::EditorConfig, db::Note, utils::parse_markdown_file};
+use crate::settings::EditorConfig;
 
 /// Returns the user's preferred editor from environment variables or config.
 /// Priority: config.default_editor > $EDITOR > vi
@@ -11,72 +13,52 @@ fn get_editor(config: &EditorConfig) -> String {
 	config.default_editor.clone().unwrap_or_else(|| env::var("EDITOR").unwrap_or_else(|_| "vi".to_string()))
 }
 
-/// Returns a consistent temp file path for qnote editing.
-/// Reusing the same path avoids filesystem overhead and is faster than creating
-/// new files.
-fn get_temp_path() -> PathBuf { env::temp_dir().join("qnote-edit.md") }
-
-/// Opens the user's editor with an empty template for creating a new note.
-/// Returns None if the user cancels or creates an empty note.
-/// Returns Some((title, content, tags)) if a valid note is created.
-pub fn open_editor_for_new_note(config: &EditorConfig) -> Result<Option<(String, String, Vec<String>)>> {
-	let temp_path = get_temp_path();
-
-	// Create file with appropriate permissions
+/// Creates the temp file a note is edited in, readable only by the user
+/// unless `secure_temp_files` is off. Notes from an `encrypted` vault always
+/// get a private file. The file is deleted when dropped, so the note's text
+/// does not outlive the edit however it ends.
+#[cfg_attr(not(unix), allow(unused_variables))]
+fn create_temp_file(config: &EditorConfig, encrypted: bool) -> Result<NamedTempFile> {
+	let mut builder = tempfile::Builder::new();
+	builder.prefix("qnote-edit-").suffix(".md");
 	#[cfg(unix)]
-	{
-		use std::os::unix::fs::OpenOptionsExt;
-		let mode = if config.secure_temp_files { 0o600 } else { 0o644 };
-		fs::OpenOptions::new().write(true).create(true).truncate(true).mode(mode).open(&temp_path)?;
-	}
-	#[cfg(not(unix))]
-	{
-		let mut file = fs::File::create(&temp_path)?;
-		file.flush()?;
-		drop(file);
+	if !config.secure_temp_files && !encrypted {
+		use std::os::unix::fs::PermissionsExt;
+		builder.permissions(fs::Permissions::from_mode(0o644));
 	}
+	builder.tempfile().context("Failed to create a temp file to edit in")
+}
 
-	open_editor(&temp_path, config)?;
+/// Opens the user's editor with an empty template for creating a new note.
+/// Returns None if the user cancels or creates an empty note.
+/// Returns the parsed note if a valid note is created.
+pub fn open_editor_for_new_note(config: &EditorConfig, encrypted: bool) -> Result<Option<ParsedNote>> {
+	let temp_file = create_temp_file(config, encrypted)?;
+	open_editor(temp_file.path(), config)?;
 
-	// Read and parse the edited content
-	let content = fs::read_to_string(&temp_path)?;
+	// Read by path: editors may replace the file rather than write to it
+	let content = fs::read_to_string(temp_file.path())?;
 
 	Ok(parse_markdown_file(&content))
 }
 
 /// Opens the user's editor with an existing note's content pre-filled.
-/// Note format: title on line 1, hashtags on line 2 (if any), content after
-/// blank line. Returns None if the user cancels or deletes all content.
-/// Returns Some((title, content, tags)) if the note is successfully edited.
-pub fn open_editor_for_edit(note: &Note, config: &EditorConfig) -> Result<Option<(String, String, Vec<String>)>> {
-	let temp_path = get_temp_path();
-
-	// Create file with appropriate permissions and use BufWriter for better I/O
-	// performance
-	#[cfg(unix)]
-	let file = {
-		use std::os::unix::fs::OpenOptionsExt;
-		let mode = if config.secure_temp_files { 0o600 } else { 0o644 };
-		fs::OpenOptions::new().write(true).create(true).truncate(true).mode(mode).open(&temp_path)?
-	};
-	#[cfg(not(unix))]
-	let file = fs::File::create(&temp_path)?;
-	let mut writer = io::BufWriter::new(file);
+/// Note format: tags and properties in a frontmatter block (if any), then
+/// title, content after blank line. Returns None if the user cancels or
+/// deletes all content. Returns the parsed note if the note is successfully
+/// edited.
+pub fn open_editor_for_edit(note: &Note, config: &EditorConfig, encrypted: bool) -> Result<Option<ParsedNote>> {
+	let mut temp_file = create_temp_file(config, encrypted)?;
+	let mut writer = io::BufWriter::new(temp_file.as_file_mut());
+
+	// Write tags and properties as frontmatter
+	if !note.tags.is_empty() || !note.properties.is_empty() {
+		write!(writer, "---\n{}{}---\n", tags_to_frontmatter(&note.tags), properties_to_frontmatter(&note.properties))?;
+	}
 
 	// Write title
 	write!(writer, "{}", note.title)?;
 
-	// Write tags if present (optimized: avoid intermediate Vec allocation)
-	if !note.tags.is_empty() {
-		writer.write_all(b"\n")?;
-		for (i, tag) in note.tags.iter().enumerate() {
-			if i > 0 {
-				writer.write_all(b" ")?;
-			}
-			write!(writer, "@{tag}")?;
-		}
-	}
-
 	// Write content if present
 	if !note.content.is_empty() {
 		writer.write_all(b"\n\n")?;
@@ -86,10 +68,10 @@ pub fn open_editor_for_edit(note: &Note, config: &EditorConfig) -> Result<Option
 	writer.flush()?;
 	drop(writer);
 
-	open_editor(&temp_path, config)?;
+	open_editor(temp_file.path(), config)?;
 
 	// Read back and parse the edited content
-	let content = fs::read_to_string(&temp_path)?;
+	let content = fs::read_to_string(temp_file.path())?;
 
 	Ok(parse_markdown_file(&content))
 }
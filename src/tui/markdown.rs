@@ -1,17 +1,26 @@
 use pulldown_cmark::{Event, HeadingLevel, Options, Parser, Tag, TagEnd};
+use qnote::utils::find_wikilinks;
 use ratatui::{style::{Color, Modifier, Style}, text::{Line, Span}};
 
-use crate::config::ThemeConfig;
+use crate::settings::ThemeConfig;
 
-/// Renders markdown to styled lines using theme colors
-pub fn markdown_to_lines(markdown: &str, theme: &ThemeConfig) -> Vec<Line<'static>> {
+/// Renders markdown to styled lines using theme colors. The wikilink at
+/// index `active_link` (in display order) is highlighted.
+pub fn markdown_to_lines(markdown: &str, theme: &ThemeConfig, active_link: Option<usize>) -> Vec<Line<'static>> {
 	if markdown.is_empty() {
 		return Vec::new();
 	}
 
-	let opts = Options::ENABLE_STRIKETHROUGH | Options::ENABLE_TASKLISTS;
-	let parser = Parser::new_ext(markdown, opts);
-	Renderer::new(theme).render(parser)
+	Renderer::new(theme, active_link).render(parser(markdown)).lines
+}
+
+/// Returns the target of every wikilink in display order, skipping code.
+pub fn wikilink_targets(markdown: &str, theme: &ThemeConfig) -> Vec<String> {
+	Renderer::new(theme, None).render(parser(markdown)).links
+}
+
+fn parser(markdown: &str) -> Parser<'_> {
+	Parser::new_ext(markdown, Options::ENABLE_STRIKETHROUGH | Options::ENABLE_TASKLISTS)
 }
 
 struct Renderer {
@@ -34,30 +43,39 @@ struct Renderer {
 	strong_color:        Color,
 	strikethrough_color: Color,
 	blockquote_color:    Color,
+	/// Consecutive text events, joined so wikilinks split by the parser
+	/// (it breaks text at brackets) can be recognized.
+	pending_text:        String,
+	active_link:         Option<usize>,
+	/// Wikilink targets seen so far.
+	links:               Vec<String>,
 }
 
 impl Renderer {
-	fn new(theme: &ThemeConfig) -> Self {
+	fn new(theme: &ThemeConfig, active_link: Option<usize>) -> Self {
 		Self {
-			lines:               Vec::new(),
-			current_line:        Vec::new(),
-			styles:              Vec::new(),
-			in_code_block:       false,
-			in_list:             false,
-			list_level:          0,
-			in_blockquote:       false,
-			item_needs_prefix:   false,
-			h1_color:            *theme.h1,
-			h2_color:            *theme.h2,
-			h3_color:            *theme.h3,
-			h4_h6_color:         *theme.h4_h6,
-			code_color:          *theme.code,
-			code_block_color:    *theme.code_block,
-			link_color:          *theme.link,
-			emphasis_color:      *theme.emphasis,
-			strong_color:        *theme.strong,
+			lines: Vec::new(),
+			current_line: Vec::new(),
+			styles: Vec::new(),
+			in_code_block: false,
+			in_list: false,
+			list_level: 0,
+			in_blockquote: false,
+			item_needs_prefix: false,
+			h1_color: *theme.h1,
+			h2_color: *theme.h2,
+			h3_color: *theme.h3,
+			h4_h6_color: *theme.h4_h6,
+			code_color: *theme.code,
+			code_block_color: *theme.code_block,
+			link_color: *theme.link,
+			emphasis_color: *theme.emphasis,
+			strong_color: *theme.strong,
 			strikethrough_color: *theme.strikethrough,
-			blockquote_color:    *theme.blockquote,
+			blockquote_color: *theme.blockquote,
+			pending_text: String::new(),
+			active_link,
+			links: Vec::new(),
 		}
 	}
 }
@@ -86,12 +104,17 @@ impl Renderer {
 		}
 	}
 
-	fn render(mut self, parser: Parser) -> Vec<Line<'static>> {
+	fn render(mut self, parser: Parser) -> Self {
 		for event in parser {
+			if let Event::Text(text) = &event {
+				self.pending_text.push_str(text);
+				continue;
+			}
+			self.flush_text();
+
 			match event {
 				Event::Start(tag) => self.start_tag(tag),
 				Event::End(tag) => self.end_tag(tag),
-				Event::Text(text) => self.text(text.into()),
 				Event::Code(code) => self.inline_code(code.into()),
 				Event::SoftBreak => self.push_span(" "),
 				Event::HardBreak => self.finish_line(),
@@ -99,19 +122,25 @@ impl Renderer {
 					self.finish_line();
 					self.lines.push(Line::from(Span::styled("─".repeat(80), Style::default().fg(Color::DarkGray))));
 				}
-				Event::TaskListMarker(checked) => {
-					if self.item_needs_prefix {
-						let indent = "  ".repeat(self.list_level.saturating_sub(1));
-						let marker = if checked { "[✓] " } else { "[ ] " };
-						self.current_line.push(Span::raw(format!("{indent}{marker}")));
-						self.item_needs_prefix = false;
-					}
+				Event::TaskListMarker(checked) if self.item_needs_prefix => {
+					let indent = "  ".repeat(self.list_level.saturating_sub(1));
+					let marker = if checked { "[✓] " } else { "[ ] " };
+					self.current_line.push(Span::raw(format!("{indent}{marker}")));
+					self.item_needs_prefix = false;
 				}
 				_ => {}
 			}
 		}
+		self.flush_text();
 		self.finish_line();
-		self.lines
+		self
+	}
+
+	fn flush_text(&mut self) {
+		if !self.pending_text.is_empty() {
+			let text = std::mem::take(&mut self.pending_text);
+			self.text(text);
+		}
 	}
 
 	fn start_tag(&mut self, tag: Tag) {
@@ -207,11 +236,31 @@ impl Renderer {
 			for line in text.split('\n') {
 				self.lines.push(Line::from(Span::styled(format!("  {line}"), self.style())));
 			}
-		} else if self.in_blockquote {
-			self.push_span(format!("│ {text}"));
 		} else {
-			self.push_span(text);
+			if self.in_blockquote {
+				self.push_span("│ ");
+			}
+			self.text_with_wikilinks(&text);
+		}
+	}
+
+	/// Pushes text, rendering `[[Title|label]]` as its label in link style.
+	fn text_with_wikilinks(&mut self, text: &str) {
+		let mut pos = 0;
+		for (range, target) in find_wikilinks(text) {
+			self.push_span(&text[pos..range.start]);
+
+			let inner = &text[range.start + 2..range.end - 2];
+			let label = inner.split_once('|').map(|(_, label)| label.trim()).filter(|l| !l.is_empty()).unwrap_or(target);
+			let mut style = Style::default().fg(self.link_color).add_modifier(Modifier::BOLD);
+			if self.active_link == Some(self.links.len()) {
+				style = style.add_modifier(Modifier::REVERSED);
+			}
+			self.current_line.push(Span::styled(label.to_string(), self.style().patch(style)));
+			self.links.push(target.to_string());
+			pos = range.end;
 		}
+		self.push_span(&text[pos..]);
 	}
 
 	fn inline_code(&mut self, code: String) {
@@ -7,11 +7,13 @@ use std::io;
 
 use anyhow::Result;
 pub use app::App;
+use qnote::db::Database;
 use ratatui::{Terminal, backend::CrosstermBackend, crossterm::{event::DisableMouseCapture, execute, terminal::{EnterAlternateScreen, LeaveAlternateScreen, disable_raw_mode, enable_raw_mode}}};
 
-use crate::{config::Config, db::Database};
+use crate::settings::Config;
 
-pub fn run_tui(db: Database, config: Config) -> Result<()> {
+/// Runs the TUI until the user quits, then hands the database back.
+pub fn run_tui(db: Database, config: Config) -> Result<Database> {
 	enable_raw_mode()?;
 	let mut stdout = io::stdout();
 	execute!(stdout, EnterAlternateScreen)?;
@@ -29,5 +31,5 @@ pub fn run_tui(db: Database, config: Config) -> Result<()> {
 		println!("{err:?}");
 	}
 
-	Ok(())
+	Ok(app.db)
 }
@@ -1,23 +1,41 @@
+use std::time::Duration;
+
 use anyhow::Result;
+use qnote::{db::NoteSummary, utils::{format_date_short, format_size}};
 use ratatui::{Terminal, crossterm::event::{self, Event, KeyEventKind}, layout::{Alignment, Constraint, Direction, Layout, Margin, Rect}, style::{Color, Modifier, Style}, symbols::border, text::{Line, Span}, widgets::{Block, Borders, List, ListItem, Paragraph, Wrap}};
 
-use super::{app::{App, Screen}, markdown::markdown_to_lines};
-use crate::{db::Note, utils::format_date_short};
+use super::{app::{App, Screen, SortMode, TreeNode, TreeState}, markdown::markdown_to_lines};
+use crate::settings::ThemeConfig;
 
 const LIST_BORDER_PADDING: u16 = 4;
 const UI_PADDING: u16 = 1;
 const HELP_SEARCH_MODE: &str = "^n/p navigate  ⏎ accept  ESC cancel";
 
+const HELP_TAG_INPUT: &str = "⏎ apply to selected notes  ESC cancel";
+const HELP_CONFIRM: &str = "y confirm  any other key cancel";
+const HELP_CONFLICT: &str = "m keep mine  t keep theirs  c save mine as a copy";
+
+/// How often the database is checked for changes made by other processes.
+const EXTERNAL_CHANGE_POLL: Duration = Duration::from_millis(500);
+
 fn generate_help_text(app: &App) -> String {
 	let kb = &app.config.keybindings;
+	if app.trash_view {
+		return format!(
+			"{}/{} nav  {} restore  {} delete forever  ⇧D empty trash  {}/ESC back  {} quit  ^j/k scroll  . help",
+			kb.move_down, kb.move_up, kb.restore, kb.delete, kb.trash, kb.quit
+		);
+	}
+
 	let batch_ops = if !app.selection.is_empty() {
-		format!("⇧D batch delete ({})  ⇧X batch export ({})  ⇧C clear", app.selection.len(), app.selection.len())
+		let count = app.selection.len();
+		format!("⇧D batch trash ({count})  ⇧X batch export ({count})  ⇧T/⇧U batch tag/untag ({count})  ⇧C clear")
 	} else {
-		"⇧A select all  ⇧C clear".to_string()
+		"⇧A select all  ⇧C clear  ⇧Z show archived".to_string()
 	};
 
 	format!(
-		"{}/{} nav  {} edit  {} new  {} del  {} search  SPC select  {} quit  ^j/k scroll  {}/{} top/bot  {} sort  {} export  ESC clear  . help  {}",
+		"{}/{} nav  {} edit  {} new  {} del  {} search  SPC select  {} quit  ^j/k scroll  {}/{} top/bot  {} sort  {} export  {} pin  {} archive  {} trash  {} tasks  {} tags  {} notebooks  ⇥ link  {} follow  ESC clear  . help  {}",
 		kb.move_down,
 		kb.move_up,
 		kb.edit,
@@ -29,6 +47,13 @@ fn generate_help_text(app: &App) -> String {
 		kb.goto_bottom,
 		kb.sort,
 		kb.export,
+		kb.pin,
+		kb.archive,
+		kb.trash,
+		kb.tasks,
+		kb.tags,
+		kb.notebooks,
+		kb.follow_link,
 		batch_ops
 	)
 }
@@ -39,18 +64,37 @@ pub fn run_app<B: ratatui::backend::Backend>(terminal: &mut Terminal<B>, app: &m
 			terminal.clear()?;
 			app.needs_clear = false;
 		}
+		app.sync_preview()?;
 		terminal.draw(|f| ui(f, app))?;
 
+		if !event::poll(EXTERNAL_CHANGE_POLL)? {
+			app.check_external_changes()?;
+			continue;
+		}
+
 		if let Event::Key(key) = event::read()?
 			&& key.kind == KeyEventKind::Press
 		{
 			app.tick_message();
 
 			let should_quit = match app.screen {
+				Screen::List if app.trash_view => app.handle_trash_input(key.code, key.modifiers)?,
 				Screen::List => app.handle_list_input(key.code, key.modifiers)?,
+				Screen::Confirm(action) => app.handle_confirm_input(action, key.code)?,
+				Screen::Conflict => app.handle_conflict_input(key.code)?,
 				Screen::SearchMode => app.handle_search_input(key.code, key.modifiers)?,
+				Screen::Tasks => app.handle_tasks_input(key.code, key.modifiers)?,
+				Screen::Tags => app.handle_tag_tree_input(key.code, key.modifiers)?,
+				Screen::Notebooks => app.handle_notebook_tree_input(key.code, key.modifiers)?,
+				Screen::TagInput { add } => app.handle_tag_input(add, key.code)?,
 			};
 
+			// Encrypted vaults live in memory; save each change as it is made
+			if let Err(e) = app.db.flush() {
+				let saved_elsewhere = app.db.conflict_copy().is_some();
+				app.set_message(if saved_elsewhere { e.to_string() } else { format!("Changes not saved: {e}") });
+			}
+
 			if should_quit {
 				return Ok(());
 			}
@@ -70,7 +114,12 @@ fn ui(f: &mut ratatui::Frame, app: &mut App) {
 	};
 
 	let chunks = Layout::default().direction(Direction::Vertical).constraints(constraints).split(padded_area);
-	render_split_view(f, app, chunks[0]);
+	match app.screen {
+		Screen::Tasks => render_tasks(f, app, chunks[0]),
+		Screen::Tags => render_tag_tree(f, app, chunks[0]),
+		Screen::Notebooks => render_notebook_tree(f, app, chunks[0]),
+		_ => render_split_view(f, app, chunks[0]),
+	}
 
 	if has_message {
 		render_status_bar(f, app, chunks[1]);
@@ -81,7 +130,7 @@ fn ui(f: &mut ratatui::Frame, app: &mut App) {
 }
 
 fn calculate_footer_height(app: &App, width: u16) -> u16 {
-	if app.screen == Screen::SearchMode {
+	if app.screen != Screen::List {
 		return 1;
 	}
 
@@ -110,7 +159,7 @@ fn render_status_bar(f: &mut ratatui::Frame, app: &App, area: Rect) {
 	}
 }
 
-fn highlight_title(text: &str, indices: &[usize], theme: &crate::config::ThemeConfig) -> Vec<Span<'static>> {
+fn highlight_title(text: &str, indices: &[usize], theme: &ThemeConfig) -> Vec<Span<'static>> {
 	if indices.is_empty() {
 		return vec![Span::raw(text.to_string())];
 	}
@@ -156,23 +205,28 @@ fn render_split_view(f: &mut ratatui::Frame, app: &mut App, area: Rect) {
 }
 
 struct ListItemParams<'a> {
-	note:          &'a Note,
+	note:          &'a NoteSummary,
 	idx:           usize,
 	is_hovered:    bool,
 	is_selected:   bool,
 	has_search:    bool,
 	match_indices: &'a [Vec<usize>],
+	/// Show the creation date instead of the last update.
+	show_created:  bool,
 	list_width:    usize,
-	theme:         &'a crate::config::ThemeConfig,
+	theme:         &'a ThemeConfig,
 }
 
 fn create_list_item(params: ListItemParams) -> ListItem<'static> {
-	let ListItemParams { note, idx, is_hovered, is_selected, has_search, match_indices, list_width, theme } = params;
-	let date_str = format_date_short(&note.updated_at);
+	let ListItemParams { note, idx, is_hovered, is_selected, has_search, match_indices, show_created, list_width, theme } =
+		params;
+	let date = if show_created { note.created_at } else { note.updated_at };
+	let date_str = format_date_short(&note.deleted_at.unwrap_or(date));
 	let clean_title = note.title.trim_start_matches('#').trim().to_string();
 
 	const INDICATOR_WIDTH: usize = 2;
-	let available_width = list_width.saturating_sub(date_str.len() + INDICATOR_WIDTH + 1);
+	let pin_marker = if note.pinned { "★ " } else { "" };
+	let available_width = list_width.saturating_sub(date_str.len() + INDICATOR_WIDTH + pin_marker.chars().count() + 1);
 
 	let title_display = if clean_title.len() > available_width {
 		format!("{}…", &clean_title[..available_width.saturating_sub(1)])
@@ -204,7 +258,17 @@ fn create_list_item(params: ListItemParams) -> ListItem<'static> {
 		Span::raw("  ")
 	}];
 
-	let text_color = if is_selected || is_hovered { theme.text } else { theme.unselected_text };
+	if note.pinned {
+		spans.push(Span::styled(pin_marker, Style::default().fg(*theme.hover_indicator)));
+	}
+
+	let text_color = if note.archived {
+		theme.metadata
+	} else if is_selected || is_hovered {
+		theme.text
+	} else {
+		theme.unselected_text
+	};
 	spans.extend(title_spans.into_iter().map(|span| {
 		if span.style.fg == Some(*theme.search_highlight) {
 			span
@@ -234,9 +298,10 @@ fn render_list(f: &mut ratatui::Frame, app: &mut App, area: Rect) {
 				note,
 				idx,
 				is_hovered: current_idx == Some(idx),
-				is_selected: note.id.is_some_and(|id| app.is_note_selected(id)),
+				is_selected: app.is_note_selected(note.id),
 				has_search,
 				match_indices: &app.search.match_indices,
+				show_created: matches!(app.sort_mode, SortMode::CreatedDesc | SortMode::CreatedAsc),
 				list_width,
 				theme,
 			})
@@ -244,23 +309,42 @@ fn render_list(f: &mut ratatui::Frame, app: &mut App, area: Rect) {
 		.collect();
 
 	let title = if app.screen == Screen::SearchMode {
-		format!("Search: {}_", app.search.input_buffer)
-	} else if app.search.is_active() {
-		format!("Notes (search: {})", app.search.query)
+		match &app.search.error {
+			Some(err) => format!("Search: {}_ • {err}", app.search.input_buffer),
+			None => format!("Search: {}_", app.search.input_buffer),
+		}
+	} else if let Screen::TagInput { add } = app.screen {
+		let action = if add { "Add tag to" } else { "Remove tag from" };
+		format!("{action} {} notes: {}_", app.selection.len(), app.tag_input)
+	} else if app.trash_view {
+		"Trash".to_string()
 	} else {
-		"Notes".to_string()
+		let filters = [
+			app.notebook_filter.as_ref().map(|filter| format!("notebook: {}", filter.path)),
+			app.tag_filter.as_ref().map(|tag| format!("tag: {tag}")),
+			app.search.is_active().then(|| format!("search: {}", app.search.query)),
+		];
+		let filters = filters.into_iter().flatten().collect::<Vec<_>>();
+		if filters.is_empty() { "Notes".to_string() } else { format!("Notes ({})", filters.join(", ")) }
 	};
 
-	let stats = if !app.selection.is_empty() {
-		format!("{} notes • {} selected", app.notes.len(), app.selection.len())
+	let stats = if app.trash_view {
+		format!("{} notes in trash", app.note_count)
+	} else if !app.selection.is_empty() {
+		format!("{} notes • {} selected", app.note_count, app.selection.len())
 	} else if app.search.is_active() {
-		format!("{} matches", app.notes.len())
+		format!("{} matches", app.note_count)
+	} else if app.show_archived {
+		format!("{} notes • {} • archived shown", app.note_count, app.sort_mode.name())
 	} else {
-		format!("{} notes • {}", app.notes.len(), app.sort_mode.name())
+		format!("{} notes • {}", app.note_count, app.sort_mode.name())
 	};
 
-	let title_style =
-		if app.screen == Screen::SearchMode { Style::default().fg(*theme.hover_indicator) } else { Style::default() };
+	let title_style = if matches!(app.screen, Screen::SearchMode | Screen::TagInput { .. }) {
+		Style::default().fg(*theme.hover_indicator)
+	} else {
+		Style::default()
+	};
 
 	let list = List::new(items)
 		.block(
@@ -280,28 +364,64 @@ fn render_preview(f: &mut ratatui::Frame, app: &App, area: Rect) {
 	let theme = &app.config.theme;
 	let overlay_color = Style::default().fg(*theme.metadata);
 
-	if let Some(note) = app.get_selected_note() {
-		let metadata = if note.tags.is_empty() {
+	if let Some(note) = app.preview_note() {
+		let mut metadata = if note.tags.is_empty() {
 			format_date_short(&note.updated_at)
 		} else {
 			format!("{} • {}", note.tags.join(", "), format_date_short(&note.updated_at))
 		};
+		if note.pinned {
+			metadata.push_str(" • pinned");
+		}
+		if note.archived {
+			metadata.push_str(" • archived");
+		}
 
 		let clean_title = note.title.trim_start_matches('#').trim();
 		let teal_bold = Style::default().fg(*theme.hover_indicator).add_modifier(Modifier::BOLD);
 
-		let content_lines = vec![
+		let mut content_lines = vec![
 			Line::from(vec![Span::raw("  "), Span::styled(clean_title, teal_bold)]),
 			Line::from(vec![Span::raw("  "), Span::styled(metadata, overlay_color)]),
-			Line::from(""),
-		]
-		.into_iter()
-		.chain(markdown_to_lines(&note.content, theme).into_iter().map(|line| {
+		];
+		if !note.properties.is_empty() {
+			let properties = note.properties.iter().map(|(key, value)| format!("{key}: {value}")).collect::<Vec<_>>();
+			content_lines.push(Line::from(vec![Span::raw("  "), Span::styled(properties.join(" • "), overlay_color)]));
+		}
+		content_lines.push(Line::from(""));
+		content_lines.extend(markdown_to_lines(&note.content, theme, app.active_link()).into_iter().map(|line| {
 			let mut padded_spans = vec![Span::raw("  ")];
 			padded_spans.extend(line.spans);
 			Line::from(padded_spans)
-		}))
-		.collect::<Vec<_>>();
+		}));
+
+		let attachments = &app.attachments;
+		if !attachments.is_empty() {
+			content_lines.push(Line::from(""));
+			content_lines.push(Line::from(vec![
+				Span::raw("  "),
+				Span::styled(format!("Attachments ({})", attachments.len()), overlay_color.add_modifier(Modifier::BOLD)),
+			]));
+			content_lines.extend(attachments.iter().map(|attachment| {
+				Line::from(vec![
+					Span::raw("  "),
+					Span::raw(format!("• {}  ", attachment.filename)),
+					Span::styled(format!("{} • {}", format_size(attachment.size), attachment.mime_type), overlay_color),
+				])
+			}));
+		}
+
+		let backlinks = &app.backlinks;
+		if !backlinks.is_empty() {
+			content_lines.push(Line::from(""));
+			content_lines.push(Line::from(vec![
+				Span::raw("  "),
+				Span::styled(format!("Backlinks ({})", backlinks.len()), overlay_color.add_modifier(Modifier::BOLD)),
+			]));
+			content_lines.extend(backlinks.iter().map(|source| {
+				Line::from(vec![Span::raw("  "), Span::styled(format!("← {}", source.title), Style::default().fg(*theme.link))])
+			}));
+		}
 
 		let note_idx = app.list_state.selected().unwrap_or(0) + 1;
 		#[allow(clippy::cast_possible_truncation)]
@@ -310,7 +430,7 @@ fn render_preview(f: &mut ratatui::Frame, app: &App, area: Rect) {
 
 		let scroll_indicator = if app.preview_scroll > 0 {
 			let max_scroll = content_height.saturating_sub(visible_height);
-			let scroll_pct = if max_scroll > 0 { (app.preview_scroll * 100 / max_scroll).min(100) } else { 0 };
+			let scroll_pct = (app.preview_scroll * 100).checked_div(max_scroll).map_or(0, |pct| pct.min(100));
 			format!(" ↓{}%", scroll_pct)
 		} else {
 			String::new()
@@ -320,7 +440,7 @@ fn render_preview(f: &mut ratatui::Frame, app: &App, area: Rect) {
 			.borders(Borders::ALL)
 			.border_set(border::ROUNDED)
 			.title(format!("Preview{}", scroll_indicator))
-			.title_bottom(Span::styled(format!("{}/{}", note_idx, app.notes.len()), overlay_color));
+			.title_bottom(Span::styled(format!("{}/{}", note_idx, app.note_count), overlay_color));
 
 		f.render_widget(
 			Paragraph::new(content_lines).block(block).scroll((app.preview_scroll, 0)).wrap(Wrap { trim: false }),
@@ -336,6 +456,118 @@ fn render_preview(f: &mut ratatui::Frame, app: &App, area: Rect) {
 	}
 }
 
+fn render_tasks(f: &mut ratatui::Frame, app: &mut App, area: Rect) {
+	let theme = &app.config.theme;
+	let metadata = Style::default().fg(*theme.metadata);
+	let current_idx = app.tasks.list_state.selected();
+	let today = chrono::Local::now().date_naive();
+
+	let items: Vec<ListItem> = app
+		.tasks
+		.items
+		.iter()
+		.enumerate()
+		.map(|(idx, task)| {
+			let indicator = if current_idx == Some(idx) {
+				Span::styled("▎ ", Style::default().fg(*theme.hover_indicator).add_modifier(Modifier::BOLD))
+			} else {
+				Span::raw("  ")
+			};
+			let (marker, text_color) = if task.done { ("[✓] ", theme.metadata) } else { ("[ ] ", theme.text) };
+			let mut spans =
+				vec![indicator, Span::raw(marker), Span::styled(task.text.clone(), Style::default().fg(*text_color))];
+			if let Some(due) = task.due {
+				let overdue = !task.done && due < today;
+				let style = if overdue { Style::default().fg(Color::Red) } else { metadata };
+				spans.push(Span::styled(format!("  due {}", due.format("%Y-%m-%d")), style));
+			}
+			spans.push(Span::styled(format!("  — {}", task.note_title), metadata));
+			ListItem::new(Line::from(spans))
+		})
+		.collect();
+
+	let open = app.tasks.items.iter().filter(|task| !task.done).count();
+	let stats = if app.tasks.show_done {
+		format!("{} tasks • {open} open", app.tasks.items.len())
+	} else {
+		format!("{open} open tasks")
+	};
+	let list = List::new(items).block(
+		Block::default()
+			.borders(Borders::ALL)
+			.border_set(border::ROUNDED)
+			.title("Tasks")
+			.title_bottom(Span::styled(stats, metadata)),
+	);
+
+	f.render_stateful_widget(list, area, &mut app.tasks.list_state);
+}
+
+fn render_tag_tree(f: &mut ratatui::Frame, app: &mut App, area: Rect) {
+	let stats = format!("{} tags", app.tag_tree.total());
+	render_tree(f, &app.config.theme, &mut app.tag_tree, "Tags", stats, app.tag_filter.as_deref(), area);
+}
+
+fn render_notebook_tree(f: &mut ratatui::Frame, app: &mut App, area: Rect) {
+	let stats = format!("{} notebooks", app.notebook_tree.total());
+	let active = app.notebook_filter.as_ref().map(|filter| filter.path.as_str());
+	render_tree(f, &app.config.theme, &mut app.notebook_tree, "Notebooks", stats, active, area);
+}
+
+/// Renders a collapsible tree with each node's note count, highlighting the
+/// node at `active` (the one the note list is filtered by).
+fn render_tree<N: TreeNode>(
+	f: &mut ratatui::Frame,
+	theme: &ThemeConfig,
+	tree: &mut TreeState<N>,
+	title: &str,
+	stats: String,
+	active: Option<&str>,
+	area: Rect,
+) {
+	let metadata = Style::default().fg(*theme.metadata);
+	let current_idx = tree.list_state.selected();
+
+	let visible = tree.visible();
+	let items: Vec<ListItem> = visible
+		.iter()
+		.enumerate()
+		.map(|(idx, (depth, node))| {
+			let indicator = if current_idx == Some(idx) {
+				Span::styled("▎ ", Style::default().fg(*theme.hover_indicator).add_modifier(Modifier::BOLD))
+			} else {
+				Span::raw("  ")
+			};
+			let marker = match (node.children().is_empty(), tree.expanded.contains(node.path())) {
+				(true, _) => "  ",
+				(false, true) => "▾ ",
+				(false, false) => "▸ ",
+			};
+			let style = if active == Some(node.path()) {
+				Style::default().fg(*theme.active_indicator).add_modifier(Modifier::BOLD)
+			} else {
+				Style::default().fg(*theme.text)
+			};
+			ListItem::new(Line::from(vec![
+				indicator,
+				Span::raw(format!("{}{marker}", "  ".repeat(*depth))),
+				Span::styled(node.name().to_string(), style),
+				Span::styled(format!("  {}", node.count()), metadata),
+			]))
+		})
+		.collect();
+
+	let list = List::new(items).block(
+		Block::default()
+			.borders(Borders::ALL)
+			.border_set(border::ROUNDED)
+			.title(title)
+			.title_bottom(Span::styled(stats, metadata)),
+	);
+
+	f.render_stateful_widget(list, area, &mut tree.list_state);
+}
+
 fn render_help(f: &mut ratatui::Frame, app: &App, area: Rect) {
 	let theme = &app.config.theme;
 	let help_color = Style::default().fg(*theme.metadata);
@@ -369,6 +601,28 @@ fn render_help(f: &mut ratatui::Frame, app: &App, area: Rect) {
 			}
 		}
 		Screen::SearchMode => vec![Line::from(Span::styled(HELP_SEARCH_MODE, help_color))],
+		Screen::TagInput { .. } => vec![Line::from(Span::styled(HELP_TAG_INPUT, help_color))],
+		Screen::Confirm(_) => vec![Line::from(Span::styled(HELP_CONFIRM, help_color))],
+		Screen::Conflict => vec![Line::from(Span::styled(HELP_CONFLICT, help_color))],
+		Screen::Tasks => {
+			let kb = &app.config.keybindings;
+			let help_text = format!(
+				"{}/{} nav  SPC/x toggle  ⏎ open note  a show done  {}/ESC back  {} quit",
+				kb.move_down, kb.move_up, kb.tasks, kb.quit
+			);
+			vec![Line::from(Span::styled(help_text, help_color))]
+		}
+		Screen::Tags | Screen::Notebooks => {
+			let kb = &app.config.keybindings;
+			let help_text = format!(
+				"{}/{} nav  ←/→ collapse/expand  SPC toggle  ⏎ show notes  {}/ESC back  {} quit",
+				kb.move_down,
+				kb.move_up,
+				if app.screen == Screen::Tags { kb.tags } else { kb.notebooks },
+				kb.quit
+			);
+			vec![Line::from(Span::styled(help_text, help_color))]
+		}
 	};
 
 	f.render_widget(Paragraph::new(lines).alignment(Alignment::Center), area);
@@ -1,20 +1,59 @@
 //! Conversion utilities for notes and database operations.
 
-use anyhow::Result;
+use std::collections::BTreeMap;
 
-use crate::db::{Database, Note};
+use crate::db::{Note, PropertyValue};
 
-/// Formats a note as markdown content with title, tags, and body.
+/// Formats a note as markdown content with a frontmatter block holding its
+/// UUID, tags and properties, followed by title and body. The body is written
+/// as is: with frontmatter present, [`parse_markdown_file`] reads tags only
+/// from it, so the file imports back unchanged.
 /// Used for exporting notes to .md files.
 ///
 /// Format:
 /// ```markdown
+/// ---
+/// uuid: 67e55044-10b1-426f-9247-bb680e5fe0c8
+/// tags:
+///   - work/infra
+/// status: done
+/// ---
 /// Title
-/// #tag1 #tag2
 ///
 /// Content body...
 /// ```
+///
+/// [`parse_markdown_file`]: crate::utils::parse_markdown_file
 pub fn note_to_markdown(note: &Note) -> String {
+	let mut markdown = format!(
+		"---\nuuid: {}\n{}{}---\n{}",
+		note.uuid,
+		tags_to_frontmatter(&note.tags),
+		properties_to_frontmatter(&note.properties),
+		note.title
+	);
+	if !note.content.is_empty() {
+		markdown.push_str("\n\n");
+		markdown.push_str(&note.content);
+	}
+	markdown
+}
+
+/// Formats tags as a frontmatter `tags:` list, or nothing if there are none.
+pub fn tags_to_frontmatter(tags: &[String]) -> String {
+	if tags.is_empty() {
+		return String::new();
+	}
+	tags.iter().fold("tags:\n".to_string(), |list, tag| list + "  - " + tag + "\n")
+}
+
+/// Formats properties as `key: value` frontmatter lines.
+pub fn properties_to_frontmatter(properties: &BTreeMap<String, PropertyValue>) -> String {
+	properties.iter().map(|(key, value)| format!("{key}: {}\n", value.to_source())).collect()
+}
+
+/// Formats a note's title, tags, and body without frontmatter.
+pub fn note_to_markdown_body(note: &Note) -> String {
 	let mut content = note.title.clone();
 
 	if !note.tags.is_empty() {
@@ -30,43 +69,3 @@ pub fn note_to_markdown(note: &Note) -> String {
 
 	content
 }
-
-/// Resolves a note by ID or title pattern.
-/// Returns the note ID if found, or an error if ambiguous/not found.
-///
-/// This function supports flexible note identification:
-/// - Direct numeric ID: "42" -> finds note with ID 42
-/// - Title pattern (case-insensitive): "groceries" -> finds notes containing
-///   "groceries"
-///
-/// If multiple notes match a title pattern, returns an error with suggestions.
-pub fn resolve_note(db: &Database, id_or_title: &str) -> Result<i64> {
-	// Try parsing as ID first
-	if let Ok(id) = id_or_title.parse::<i64>() {
-		// Verify the ID exists
-		if db.get_note(id)?.is_some() {
-			return Ok(id);
-		}
-		anyhow::bail!("Note with ID {id} not found");
-	}
-
-	// Search by title pattern (case-insensitive)
-	let all_notes = db.list_notes()?;
-	let matches: Vec<Note> =
-		all_notes.into_iter().filter(|n| n.title.to_lowercase().contains(&id_or_title.to_lowercase())).collect();
-
-	match matches.len() {
-		0 => anyhow::bail!("No notes found matching '{id_or_title}'"),
-		1 => matches[0].id.ok_or_else(|| anyhow::anyhow!("Note missing ID")),
-		_ => {
-			eprintln!("Multiple notes found matching '{id_or_title}':");
-			for note in &matches {
-				if let Some(id) = note.id {
-					let title = &note.title;
-					eprintln!("  [{id}] {title}");
-				}
-			}
-			anyhow::bail!("Please specify a more specific pattern or use the exact ID")
-		}
-	}
-}
@@ -19,11 +19,26 @@ pub mod date_formats {
 ///
 /// # Examples
 /// ```
+/// # use qnote::utils::sanitize_filename;
 /// assert_eq!(sanitize_filename("My Note"), "My_Note");
 /// assert_eq!(sanitize_filename("Path/To/Note"), "Path-To-Note");
 /// ```
 pub fn sanitize_filename(title: &str) -> String { title.replace('/', "-").replace(' ', "_") }
 
+/// Formats a byte count for display.
+/// Returns: "512 B", "3.4 KB", "1.2 MB"
+pub fn format_size(bytes: u64) -> String {
+	#[allow(clippy::cast_precision_loss)]
+	let size = bytes as f64;
+	if bytes < 1024 {
+		format!("{bytes} B")
+	} else if bytes < 1024 * 1024 {
+		format!("{:.1} KB", size / 1024.0)
+	} else {
+		format!("{:.1} MB", size / (1024.0 * 1024.0))
+	}
+}
+
 /// Formats a datetime for list view display (short format).
 /// Returns: "Jan 15"
 pub fn format_date_short(dt: &DateTime<Utc>) -> String { dt.format(date_formats::SHORT).to_string() }
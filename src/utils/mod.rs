@@ -1,11 +1,10 @@
-//! Shared utility functions used across CLI and TUI modules.
+//! Markdown parsing and conversion for notes, plus the formatting helpers
+//! shared by the CLI and TUI.
 
 mod conversion;
 mod formatting;
-mod interaction;
 mod parsing;
 
-pub use conversion::{note_to_markdown, resolve_note};
-pub use formatting::{format_date_full, format_date_only, format_date_short, sanitize_filename};
-pub use interaction::confirm;
-pub use parsing::{parse_markdown_file, parse_tags};
+pub use conversion::{note_to_markdown, note_to_markdown_body, properties_to_frontmatter, tags_to_frontmatter};
+pub use formatting::{format_date_full, format_date_only, format_date_short, format_size, sanitize_filename};
+pub use parsing::{ParsedNote, TaskItem, extract_tasks, extract_wikilinks, find_wikilinks, normalize_tag, parse_date, parse_markdown_file, parse_property_filter, parse_tags, set_task_checkbox};
@@ -1,13 +1,25 @@
-//! Parsing utilities for markdown and tags.
+//! Parsing utilities for markdown, tags, wikilinks and tasks.
 
-/// Extracts @tags from text and returns (cleaned_text, tags)
+use std::{collections::BTreeMap, ops::Range};
+
+use anyhow::Result;
+use chrono::{DateTime, Days, Local, NaiveDate, Utc};
+use pulldown_cmark::{Event, Parser, Tag, TagEnd};
+use uuid::Uuid;
+
+use crate::db::{Note, PropertyValue, validate_property_key};
+
+/// Extracts @tags from text and returns (cleaned_text, tags). Tags may be
+/// nested with `/`, e.g. `@work/infra/k8s`. An `@` inside a word, as in an
+/// email address, does not start a tag.
 fn extract_tags(text: &str) -> (String, Vec<String>) {
 	let mut result = String::with_capacity(text.len());
 	let mut tags = Vec::new();
 	let mut chars = text.chars().peekable();
+	let mut previous = None;
 
 	while let Some(ch) = chars.next() {
-		if ch == '@' {
+		if ch == '@' && !previous.is_some_and(|c: char| c.is_alphanumeric() || c == '_') {
 			// Check if next char is alphanumeric or underscore
 			if chars.peek().is_some_and(|c| c.is_alphanumeric() || *c == '_') {
 				let mut tag = String::new();
@@ -15,48 +27,177 @@ fn extract_tags(text: &str) -> (String, Vec<String>) {
 					if c.is_alphanumeric() || c == '_' {
 						tag.push(c);
 						chars.next();
+					} else if c == '/' && chars.clone().nth(1).is_some_and(|next| next.is_alphanumeric() || next == '_') {
+						// A slash only continues the tag if another segment follows
+						tag.push(c);
+						chars.next();
 					} else {
 						break;
 					}
 				}
+				previous = tag.chars().last();
 				if !tag.is_empty() {
 					tags.push(tag);
 				}
-			} else {
-				result.push(ch);
+				continue;
 			}
+			result.push(ch);
 		} else {
 			result.push(ch);
 		}
+		previous = Some(ch);
 	}
 
 	(result, tags)
 }
 
+/// A note read from a markdown file.
+#[derive(Debug, Clone)]
+pub struct ParsedNote {
+	pub title:      String,
+	pub content:    String,
+	pub tags:       Vec<String>,
+	/// UUID from the frontmatter, if the file was exported by qnote.
+	pub uuid:       Option<String>,
+	/// Every other frontmatter field.
+	pub properties: BTreeMap<String, PropertyValue>,
+}
+
+impl ParsedNote {
+	/// Builds a new note from the parsed file, keeping its UUID if it has one.
+	pub fn into_note(self) -> Note {
+		let mut note = Note::new(self.title, self.content, self.tags);
+		if let Some(uuid) = self.uuid {
+			note.uuid = uuid;
+		}
+		note.properties = self.properties;
+		note
+	}
+}
+
+/// Fields read from a file's frontmatter.
+#[derive(Default)]
+struct Frontmatter {
+	uuid:       Option<String>,
+	tags:       Vec<String>,
+	properties: BTreeMap<String, PropertyValue>,
+}
+
+/// Splits a leading `---` frontmatter block off a file along with the
+/// remaining text. A block holding anything but fields qnote understands,
+/// such as prose between two `---` rules, is left in the text.
+fn split_frontmatter(content: &str) -> (Option<Frontmatter>, &str) {
+	let Some(rest) = content.strip_prefix("---\n").or_else(|| content.strip_prefix("---\r\n")) else {
+		return (None, content);
+	};
+
+	let mut offset = 0;
+	for line in rest.split_inclusive('\n') {
+		if line.trim_end() == "---" {
+			return match parse_frontmatter(&rest[..offset]) {
+				Some(frontmatter) => (Some(frontmatter), &rest[offset + line.len()..]),
+				None => (None, content),
+			};
+		}
+		offset += line.len();
+	}
+	(None, content)
+}
+
+/// Reads a frontmatter block made of `key: value` lines: `uuid`, `tags`
+/// (`tags: a, b`, `tags: [a, b]` or a `- a` list below the key) and
+/// properties. Returns None if any line is something else, or if the block
+/// is empty.
+fn parse_frontmatter(block: &str) -> Option<Frontmatter> {
+	let mut frontmatter = Frontmatter::default();
+	let mut lines = block.lines().filter(|line| !line.trim().is_empty()).peekable();
+	lines.peek()?;
+
+	while let Some(line) = lines.next() {
+		let (key, value) = line.split_once(':')?;
+		if key.is_empty() || key.starts_with(char::is_whitespace) || !(value.is_empty() || value.starts_with(' ')) {
+			return None;
+		}
+		let (key, value) = (key.trim_end(), value.trim());
+		let mut items = Vec::new();
+		while let Some(item) = lines.next_if(|line| line.trim_start().starts_with('-')) {
+			items.push(item.trim_start()[1..].trim());
+		}
+
+		match key {
+			"uuid" if items.is_empty() => frontmatter.uuid = Some(Uuid::parse_str(value).ok()?.to_string()),
+			"tags" if items.is_empty() || value.is_empty() => {
+				if items.is_empty() {
+					let inline = value.strip_prefix('[').and_then(|v| v.strip_suffix(']')).unwrap_or(value);
+					items = inline.split(',').collect();
+				}
+				for item in items {
+					let tag = normalize_tag(item.trim().trim_matches(['"', '\'']).trim_start_matches('@'));
+					if !tag.is_empty() && !frontmatter.tags.contains(&tag) {
+						frontmatter.tags.push(tag);
+					}
+				}
+			}
+			_ if items.is_empty() && !value.is_empty() && validate_property_key(key).is_ok() => {
+				frontmatter.properties.insert(key.to_string(), PropertyValue::parse(value));
+			}
+			_ => return None,
+		}
+	}
+	Some(frontmatter)
+}
+
 /// Parses a markdown file according to qnote's format.
 ///
 /// Expected format:
+/// - Optional frontmatter between `---` lines: `uuid: ...`, `tags: ...` and
+///   `key: value` properties
 /// - Line 1: Title (required, but can be empty - will use fallback)
 /// - Remaining lines: Note content (body)
-/// - Tags can appear anywhere in content using @tag format
+/// - Without frontmatter, tags can appear anywhere in content using @tag format
 ///
 /// Returns None if the note is completely empty.
-/// Returns Some((title, content, tags)) for a valid note.
 /// If no explicit title is provided, generates one from the content.
-/// Tags are extracted from entire content and removed from final content.
-pub fn parse_markdown_file(content: &str) -> Option<(String, String, Vec<String>)> {
+/// Without frontmatter, @tags are extracted from the entire content and
+/// removed from it. With frontmatter, tags come from its `tags` field only and
+/// the content is kept as written, so `@` in prose and email addresses
+/// survives an export and import. (Files exported before tags moved to the
+/// frontmatter have an @tag line right below the title, which is still read.)
+pub fn parse_markdown_file(content: &str) -> Option<ParsedNote> {
+	let (frontmatter, content) = split_frontmatter(content.trim_start());
+	let has_frontmatter = frontmatter.is_some();
+	let Frontmatter { uuid, mut tags, properties } = frontmatter.unwrap_or_default();
+
 	let content = content.trim();
 	if content.is_empty() {
 		return None;
 	}
 
-	let mut lines = content.lines();
+	let mut lines = content.lines().peekable();
 	let mut title = lines.next()?.trim().to_string();
+
+	// Earlier exports put the tags on an @tag line right below the title
+	let mut legacy_tags = Vec::new();
+	if has_frontmatter
+		&& tags.is_empty()
+		&& let Some((rest, line_tags)) = lines.peek().map(|line| extract_tags(line))
+		&& rest.trim().is_empty()
+		&& !line_tags.is_empty()
+	{
+		legacy_tags = line_tags;
+		lines.next();
+	}
 	let remaining_content = lines.collect::<Vec<_>>().join("\n");
 
-	// Extract @tags from content
-	let (note_content, tags) = extract_tags(&remaining_content);
+	// Extract @tags from content, unless the frontmatter lists the tags
+	let (note_content, inline_tags) =
+		if has_frontmatter { (remaining_content, legacy_tags) } else { extract_tags(&remaining_content) };
 	let note_content = note_content.trim().to_string();
+	for tag in inline_tags {
+		if !tags.contains(&tag) {
+			tags.push(tag);
+		}
+	}
 
 	// Title fallback: if no title, generate from content
 	if title.is_empty() {
@@ -75,11 +216,203 @@ pub fn parse_markdown_file(content: &str) -> Option<(String, String, Vec<String>
 			.to_string();
 	}
 
-	Some((title, note_content, tags))
+	Some(ParsedNote { title, content: note_content, tags, uuid, properties })
 }
 
 /// Parses a comma-separated string of tags into a vector.
 /// Trims whitespace and filters out empty strings.
 pub fn parse_tags(tags: Option<String>) -> Vec<String> {
-	tags.map(|t| t.split(',').map(|s| s.trim().to_string()).filter(|s| !s.is_empty()).collect()).unwrap_or_default()
+	tags.map(|t| t.split(',').map(normalize_tag).filter(|s| !s.is_empty()).collect()).unwrap_or_default()
+}
+
+/// Trims a tag and drops empty `/` segments, so `" work//infra/ "` becomes
+/// `"work/infra"`.
+pub fn normalize_tag(tag: &str) -> String {
+	tag.split('/').map(str::trim).filter(|segment| !segment.is_empty()).collect::<Vec<_>>().join("/")
+}
+
+/// Parses a date given on the command line, either `YYYY-MM-DD` (local time)
+/// or a full RFC 3339 timestamp. A plain date stands for the start of that
+/// day, or the start of the next day if `end_of_day` is set, so it can be used
+/// as an exclusive upper bound.
+pub fn parse_date(input: &str, end_of_day: bool) -> Result<DateTime<Utc>> {
+	if let Ok(timestamp) = DateTime::parse_from_rfc3339(input) {
+		return Ok(timestamp.with_timezone(&Utc));
+	}
+
+	let date = NaiveDate::parse_from_str(input, "%Y-%m-%d")
+		.map_err(|_| anyhow::anyhow!("Invalid date '{input}' (expected YYYY-MM-DD or RFC 3339)"))?;
+	let date = if end_of_day { date.checked_add_days(Days::new(1)).unwrap_or(date) } else { date };
+	date
+		.and_hms_opt(0, 0, 0)
+		.and_then(|midnight| midnight.and_local_timezone(Local).earliest())
+		.map(|local| local.with_timezone(&Utc))
+		.ok_or_else(|| anyhow::anyhow!("Invalid date '{input}'"))
+}
+
+/// Parses a `key=value` property filter given on the command line.
+pub fn parse_property_filter(input: &str) -> Result<(String, PropertyValue)> {
+	let (key, value) =
+		input.split_once('=').ok_or_else(|| anyhow::anyhow!("Invalid filter '{input}' (expected KEY=VALUE)"))?;
+	let key = key.trim();
+	validate_property_key(key)?;
+	Ok((key.to_string(), PropertyValue::parse(value)))
+}
+
+/// Finds `[[Title]]` and `[[Title|label]]` wikilinks in text.
+///
+/// Returns the byte range of each link (brackets included) and its target
+/// title. Links that span lines or have an empty target are ignored.
+pub fn find_wikilinks(text: &str) -> Vec<(Range<usize>, &str)> {
+	let mut links = Vec::new();
+	let mut pos = 0;
+
+	while let Some(start) = text[pos..].find("[[").map(|i| pos + i) {
+		let inner_start = start + 2;
+		let Some(len) = text[inner_start..].find("]]") else {
+			break;
+		};
+		let inner = &text[inner_start..inner_start + len];
+		if let Some(nested) = inner.rfind("[[") {
+			// Retry from the innermost opening brackets, e.g. "[[a [[b]]"
+			pos = inner_start + nested;
+			continue;
+		}
+
+		let end = inner_start + len + 2;
+		let target = inner.split('|').next().unwrap_or_default().trim();
+		if !inner.contains('\n') && !target.is_empty() {
+			links.push((start..end, target));
+		}
+		pos = end;
+	}
+
+	links
+}
+
+/// A `- [ ] task` list item in a note.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TaskItem {
+	/// 1-based line number within the note content.
+	pub line: usize,
+	/// Task text, without the `due:` date.
+	pub text: String,
+	pub done: bool,
+	/// Date given as `due:YYYY-MM-DD` in the task text.
+	pub due:  Option<NaiveDate>,
+}
+
+/// Finds the checkbox of a task list item (`- [ ]`, `* [x]`, `1. [ ]`, ...).
+/// Returns the byte offset of the box's `[` and whether it is checked.
+fn task_checkbox(line: &str) -> Option<(usize, bool)> {
+	let trimmed = line.trim_start();
+	let after_marker = match trimmed.strip_prefix(['-', '*', '+']) {
+		Some(rest) => rest,
+		None => {
+			let digits = trimmed.len() - trimmed.trim_start_matches(|c: char| c.is_ascii_digit()).len();
+			if digits == 0 {
+				return None;
+			}
+			trimmed[digits..].strip_prefix(['.', ')'])?
+		}
+	};
+	let checkbox = after_marker.strip_prefix([' ', '\t'])?.trim_start_matches([' ', '\t']);
+	let done = match checkbox.get(..3)? {
+		"[ ]" => false,
+		"[x]" | "[X]" => true,
+		_ => return None,
+	};
+	if !checkbox[3..].is_empty() && !checkbox[3..].starts_with(char::is_whitespace) {
+		return None;
+	}
+	Some((line.len() - checkbox.len(), done))
+}
+
+/// Finds the task list items in a note's content, skipping fenced code
+/// blocks.
+pub fn extract_tasks(content: &str) -> Vec<TaskItem> {
+	let mut tasks = Vec::new();
+	let mut in_fence = false;
+
+	for (index, line) in content.lines().enumerate() {
+		let trimmed = line.trim_start();
+		if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+			in_fence = !in_fence;
+			continue;
+		}
+		let Some((start, done)) = task_checkbox(line).filter(|_| !in_fence) else { continue };
+
+		let mut due = None;
+		let words: Vec<&str> = line[start + 3..]
+			.split_whitespace()
+			.filter(|word| {
+				let date = word.strip_prefix("due:").and_then(|d| NaiveDate::parse_from_str(d, "%Y-%m-%d").ok());
+				due = due.or(date);
+				date.is_none()
+			})
+			.collect();
+		tasks.push(TaskItem { line: index + 1, text: words.join(" "), done, due });
+	}
+
+	tasks
+}
+
+/// Returns `content` with the task on `line` (1-based) checked or unchecked,
+/// or None if that line holds no task.
+pub fn set_task_checkbox(content: &str, line: usize, done: bool) -> Option<String> {
+	if !extract_tasks(content).iter().any(|task| task.line == line) {
+		return None;
+	}
+
+	let mut result = String::with_capacity(content.len());
+	for (index, text) in content.split_inclusive('\n').enumerate() {
+		match task_checkbox(text).filter(|_| index + 1 == line) {
+			Some((start, _)) => {
+				result.push_str(&text[..start]);
+				result.push_str(if done { "[x]" } else { "[ ]" });
+				result.push_str(&text[start + 3..]);
+			}
+			None => result.push_str(text),
+		}
+	}
+	Some(result)
+}
+
+/// Returns the distinct wikilink targets in a note, in order of appearance.
+/// Brackets inside code spans and code blocks are not links.
+pub fn extract_wikilinks(content: &str) -> Vec<String> {
+	let mut targets: Vec<String> = Vec::new();
+	for text in prose_runs(content) {
+		for (_, target) in find_wikilinks(&text) {
+			if !targets.iter().any(|t| t.eq_ignore_ascii_case(target)) {
+				targets.push(target.to_string());
+			}
+		}
+	}
+	targets
+}
+
+/// Splits markdown into the runs of text outside code, joining the pieces
+/// the parser reads as separate events, so `[[Title]]` stays in one run.
+fn prose_runs(markdown: &str) -> Vec<String> {
+	let (mut runs, mut run) = (Vec::new(), String::new());
+	let mut in_code_block = false;
+	for event in Parser::new(markdown) {
+		match event {
+			Event::Text(text) if !in_code_block => {
+				run.push_str(&text);
+				continue;
+			}
+			Event::Start(Tag::CodeBlock(_)) => in_code_block = true,
+			Event::End(TagEnd::CodeBlock) => in_code_block = false,
+			_ => {}
+		}
+		if !run.is_empty() {
+			runs.push(std::mem::take(&mut run));
+		}
+	}
+	if !run.is_empty() {
+		runs.push(run);
+	}
+	runs
 }
@@ -0,0 +1,14 @@
+//! Helpers shared by the integration tests.
+
+use qnote::{Database, Note, config::DatabaseConfig};
+
+/// Opens an empty, fully migrated database in memory.
+pub fn memory_db() -> Database {
+	Database::new(":memory:", &DatabaseConfig::default()).expect("open in-memory database")
+}
+
+/// Creates a note with the given tags and returns its ID.
+pub fn add_note(db: &Database, title: &str, content: &str, tags: &[&str]) -> i64 {
+	let tags = tags.iter().map(ToString::to_string).collect();
+	db.create_note(&Note::new(title.to_string(), content.to_string(), tags)).expect("create note")
+}
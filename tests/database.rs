@@ -0,0 +1,261 @@
+mod common;
+
+use common::{add_note, memory_db};
+use qnote::{NoteQuery, PropertyValue, db::{EditConflict, ImportOutcome, LATEST_VERSION, SortKey}, note_to_markdown, parse_markdown_file};
+
+/// Titles of the notes selected by `query`, in listing order.
+fn titles(db: &qnote::Database, query: &NoteQuery) -> Vec<String> {
+	db.query_notes(query).unwrap().into_iter().map(|note| note.title).collect()
+}
+
+#[test]
+fn new_database_is_fully_migrated() {
+	let db = memory_db();
+	assert_eq!(db.schema_version().unwrap(), LATEST_VERSION);
+}
+
+#[test]
+fn created_note_reads_back() {
+	let db = memory_db();
+	let id = add_note(&db, "Standup", "Deploy at noon", &["work", "daily"]);
+
+	let note = db.get_note(id).unwrap().expect("note exists");
+	assert_eq!(note.title, "Standup");
+	assert_eq!(note.content, "Deploy at noon");
+	assert_eq!(note.tags, ["daily", "work"]);
+	assert_eq!(db.get_note_by_uuid(&note.uuid).unwrap().and_then(|n| n.id), Some(id));
+}
+
+#[test]
+fn title_lookup_folds_unicode_case() {
+	let db = memory_db();
+	let id = add_note(&db, "ÉCOLE plan", "", &[]);
+	add_note(&db, "Other", "", &[]);
+
+	let found: Vec<i64> = db.find_by_title("école").unwrap().iter().map(|note| note.id).collect();
+	assert_eq!(found, [id]);
+}
+
+#[test]
+fn stale_update_is_refused_and_kept_in_history() {
+	let db = memory_db();
+	let id = add_note(&db, "Plan", "v1", &[]);
+	let read = db.get_note(id).unwrap().unwrap();
+
+	db.update_note(id, Some(read.updated_at), "Plan", "v2", &[], None).unwrap();
+	let err = db.update_note(id, Some(read.updated_at), "Plan", "v3", &[], None).unwrap_err();
+	assert!(err.downcast_ref::<EditConflict>().is_some());
+
+	assert_eq!(db.get_note(id).unwrap().unwrap().content, "v2");
+	let revisions = db.list_revisions(id).unwrap();
+	assert_eq!(revisions.len(), 1);
+	assert_eq!(revisions[0].content, "v1");
+}
+
+#[test]
+fn batch_tag_edits_count_as_changes() {
+	let db = memory_db();
+	let id = add_note(&db, "Plan", "v1", &["work"]);
+	let read = db.get_note(id).unwrap().unwrap();
+	std::thread::sleep(std::time::Duration::from_millis(2));
+
+	db.add_tag_to_notes(&[id], "urgent").unwrap();
+	let err = db.update_note(id, Some(read.updated_at), "Plan", "v2", &read.tags, None).unwrap_err();
+	assert!(err.downcast_ref::<EditConflict>().is_some());
+	assert_eq!(db.get_note(id).unwrap().unwrap().tags, ["urgent", "work"]);
+}
+
+#[test]
+fn reordered_tags_are_not_a_change() {
+	let db = memory_db();
+	let id = add_note(&db, "Standup", "Notes", &["work", "daily"]);
+	let tags = ["work".to_string(), "daily".to_string()];
+
+	let note = db.get_note(id).unwrap().unwrap();
+	assert!(note.has_tags(&tags));
+	db.update_note(id, None, "Standup", "Notes", &tags, None).unwrap();
+	assert!(db.list_revisions(id).unwrap().is_empty());
+}
+
+#[test]
+fn import_updates_exported_notes_by_uuid() {
+	let db = memory_db();
+	let id = add_note(&db, "Plan", "v1", &["work"]);
+	let exported = note_to_markdown(&db.get_note(id).unwrap().unwrap());
+
+	let unchanged = parse_markdown_file(&exported).unwrap().into_note();
+	let edited = parse_markdown_file(&exported.replace("v1", "v2")).unwrap().into_note();
+	let fresh = parse_markdown_file("Inbox\n\nnew").unwrap().into_note();
+	let outcomes = db.import_notes(&[unchanged, edited, fresh]).unwrap();
+
+	assert_eq!(outcomes[..2], [ImportOutcome::Unchanged(id), ImportOutcome::Updated(id)]);
+	assert!(matches!(outcomes[2], ImportOutcome::Created(new) if new != id));
+	assert_eq!(db.get_note(id).unwrap().unwrap().content, "v2");
+}
+
+#[test]
+fn exported_notes_import_back_unchanged() {
+	let db = memory_db();
+	let content = "Mail bob@example.com and ask @team-lead about @release.";
+	let id = add_note(&db, "Handover", content, &["my-tag", "work/on-call"]);
+	let exported = note_to_markdown(&db.get_note(id).unwrap().unwrap());
+
+	let outcomes = db.import_notes(&[parse_markdown_file(&exported).unwrap().into_note()]).unwrap();
+	assert_eq!(outcomes, [ImportOutcome::Unchanged(id)]);
+	let note = db.get_note(id).unwrap().unwrap();
+	assert_eq!(note.content, content);
+	assert_eq!(note.tags, ["my-tag", "work/on-call"]);
+}
+
+#[test]
+fn listings_page_and_summarize_without_reading_content() {
+	let db = memory_db();
+	let id = add_note(&db, "Plan", "\n\n  First line  \r\nSecond", &[]);
+	for title in ["B", "C"] {
+		add_note(&db, title, "", &[]);
+	}
+
+	let query = NoteQuery::new().sort(SortKey::Title, false);
+	assert_eq!(titles(&db, &query.clone().limit(Some(2)).offset(1)), ["C", "Plan"]);
+	assert_eq!(db.count_notes(&query.clone().limit(Some(1))).unwrap(), 3);
+
+	let summary = db.query_notes(&query.clone().offset(2)).unwrap().remove(0);
+	assert_eq!((summary.first_line.as_str(), summary.size), ("First line", 24));
+	db.update_note(id, None, "Plan", "Üpdated", &[], None).unwrap();
+	let summary = db.query_notes(&query.offset(2)).unwrap().remove(0);
+	assert_eq!((summary.first_line.as_str(), summary.size), ("Üpdated", 8));
+}
+
+#[test]
+fn trashed_notes_leave_listings_until_restored() {
+	let db = memory_db();
+	let id = add_note(&db, "Old", "", &[]);
+
+	assert!(db.trash_note(id).unwrap());
+	assert!(titles(&db, &NoteQuery::new()).is_empty());
+	assert_eq!(db.list_trash().unwrap().len(), 1);
+
+	assert!(db.restore_note(id).unwrap());
+	assert_eq!(titles(&db, &NoteQuery::new()), ["Old"]);
+}
+
+#[test]
+fn tag_filters_include_nested_tags() {
+	let db = memory_db();
+	add_note(&db, "Cluster", "", &["work/infra"]);
+	add_note(&db, "Review", "", &["work"]);
+	add_note(&db, "Groceries", "", &["home"]);
+	add_note(&db, "Workshop", "", &["workshop"]);
+
+	let query = NoteQuery::new().tags_any(["work".to_string()]).sort(qnote::db::SortKey::Title, false);
+	assert_eq!(titles(&db, &query), ["Cluster", "Review"]);
+}
+
+#[test]
+fn tag_edits_apply_across_notes() {
+	let db = memory_db();
+	let a = add_note(&db, "A", "", &["todo", "todo/later"]);
+	let b = add_note(&db, "B", "", &["to-do"]);
+	add_note(&db, "C", "", &["misc"]);
+
+	assert_eq!(db.merge_tags(&["todo", "to-do"], "tasks").unwrap(), 2);
+	assert_eq!(db.get_note(a).unwrap().unwrap().tags, ["tasks", "tasks/later"]);
+	assert_eq!(db.get_note(b).unwrap().unwrap().tags, ["tasks"]);
+	assert!(db.rename_tag("tasks", "tasks/sub").is_err());
+
+	assert_eq!(db.delete_tag("tasks").unwrap(), 2);
+	assert!(db.get_note(a).unwrap().unwrap().tags.is_empty());
+	assert_eq!(db.tag_counts().unwrap(), [("misc".to_string(), 1)]);
+}
+
+#[test]
+fn batch_tag_and_property_edits_keep_revisions() {
+	let db = memory_db();
+	let id = add_note(&db, "Plan", "v1", &["work", "work/infra"]);
+	add_note(&db, "Other", "", &["misc"]);
+
+	db.set_property(id, "owner", &PropertyValue::parse("ana")).unwrap();
+	db.delete_tag("work").unwrap();
+	let revisions = db.list_revisions(id).unwrap();
+	assert_eq!(revisions.len(), 2);
+	assert_eq!(revisions[0].tags, ["work", "work/infra"]);
+	assert!(revisions[1].properties.as_ref().is_some_and(|properties| properties.is_empty()));
+
+	let restored = revisions[0].to_note();
+	db.update_note(id, None, &restored.title, &restored.content, &restored.tags, Some(&restored.properties)).unwrap();
+	let note = db.get_note(id).unwrap().unwrap();
+	assert_eq!(note.tags, ["work", "work/infra"]);
+	assert_eq!(note.properties["owner"], PropertyValue::Text("ana".to_string()));
+	assert_eq!(db.list_revisions(id).unwrap().len(), 3);
+}
+
+#[test]
+fn properties_are_typed_and_filterable() {
+	let db = memory_db();
+	let id = add_note(&db, "Release", "", &[]);
+	add_note(&db, "Other", "", &[]);
+
+	db.set_property(id, "due", &PropertyValue::parse("2025-06-30")).unwrap();
+	db.set_property(id, "priority", &PropertyValue::parse("1")).unwrap();
+	assert!(db.set_property(id, "uuid", &PropertyValue::parse("x")).is_err());
+	assert_eq!(PropertyValue::parse("01234"), PropertyValue::Text("01234".to_string()));
+	assert_eq!(PropertyValue::parse("1.10"), PropertyValue::Text("1.10".to_string()));
+
+	let note = db.get_note(id).unwrap().unwrap();
+	assert_eq!(note.properties["due"].kind(), "date");
+	assert_eq!(note.properties["priority"], PropertyValue::Number(1.0));
+	assert_eq!(titles(&db, &NoteQuery::new().property("priority", PropertyValue::Number(1.0))), ["Release"]);
+}
+
+#[test]
+fn tasks_are_indexed_and_toggled_in_the_note() {
+	let db = memory_db();
+	let id = add_note(&db, "Sprint", "- [ ] write docs due:2025-01-31\n- [x] ship\n```\n- [ ] not a task\n```", &[]);
+
+	let open = db.tasks(&NoteQuery::new(), true).unwrap();
+	assert_eq!(open.len(), 1);
+	assert_eq!((open[0].line, open[0].text.as_str()), (1, "write docs"));
+	assert!(open[0].due.is_some());
+
+	assert!(db.set_task_done(id, 1, true).unwrap());
+	assert!(db.tasks(&NoteQuery::new(), true).unwrap().is_empty());
+	assert!(db.get_note(id).unwrap().unwrap().content.starts_with("- [x] write docs"));
+}
+
+#[test]
+fn notebook_filters_include_nested_notebooks() {
+	let db = memory_db();
+	let project = db.create_notebook("Work/Project X").unwrap();
+	let work = db.find_notebook("Work").unwrap().expect("parent created");
+	assert!(db.create_notebook("Work/Project X").is_err());
+
+	let a = add_note(&db, "Kickoff", "", &[]);
+	let b = add_note(&db, "Budget", "", &[]);
+	add_note(&db, "Loose", "", &[]);
+	db.move_notes_to_notebook(&[a], Some(project)).unwrap();
+	db.move_notes_to_notebook(&[b], Some(work)).unwrap();
+
+	let in_work = NoteQuery::new().notebook(Some(work)).sort(qnote::db::SortKey::Title, false);
+	assert_eq!(titles(&db, &in_work), ["Budget", "Kickoff"]);
+	assert_eq!(titles(&db, &NoteQuery::new().notebook(Some(project))), ["Kickoff"]);
+	assert_eq!(db.notebook_path(project).unwrap().as_deref(), Some("Work/Project X"));
+
+	let tree = db.notebook_tree().unwrap();
+	assert_eq!((tree[0].name.as_str(), tree[0].count), ("Work", 2));
+
+	assert_eq!(db.delete_notebook(work).unwrap(), 2);
+	assert!(db.find_notebook("Work/Project X").unwrap().is_none());
+	assert_eq!(db.get_note(a).unwrap().unwrap().notebook_id, None);
+}
+
+#[test]
+fn search_ranks_matches_and_respects_filters() {
+	let db = memory_db();
+	add_note(&db, "Deploy checklist", "Run the migrations", &["work"]);
+	add_note(&db, "Groceries", "Milk, eggs", &["home"]);
+
+	let results = db.search_notes("deploy", &NoteQuery::new()).unwrap();
+	assert_eq!(results.len(), 1);
+	assert_eq!(results[0].note.title, "Deploy checklist");
+	assert!(db.search_notes("migrations", &NoteQuery::new().tags_any(["home".to_string()])).unwrap().is_empty());
+}
@@ -0,0 +1,79 @@
+use qnote::{Note, PropertyValue, note_to_markdown, parse_markdown_file, utils::{extract_tasks, extract_wikilinks}};
+
+#[test]
+fn parses_title_tags_and_body() {
+	let parsed = parse_markdown_file("Standup\n@work @daily\n\nDeploy at noon").expect("not empty");
+	assert_eq!(parsed.title, "Standup");
+	assert_eq!(parsed.tags, ["work", "daily"]);
+	assert_eq!(parsed.content, "Deploy at noon");
+	assert!(parsed.uuid.is_none());
+}
+
+#[test]
+fn empty_file_has_no_note() {
+	assert!(parse_markdown_file("  \n\n").is_none());
+}
+
+#[test]
+fn exported_note_reads_back_the_same() {
+	let mut note = Note::new("Release".to_string(), "Ship it".to_string(), vec!["work/infra".to_string()]);
+	note.properties.insert("status".to_string(), PropertyValue::Text("done".to_string()));
+	note.properties.insert("code".to_string(), PropertyValue::Text("42".to_string()));
+
+	let parsed = parse_markdown_file(&note_to_markdown(&note)).expect("not empty");
+	assert_eq!(parsed.title, note.title);
+	assert_eq!(parsed.content, note.content);
+	assert_eq!(parsed.tags, note.tags);
+	assert_eq!(parsed.properties, note.properties);
+	assert_eq!(parsed.into_note().uuid, note.uuid);
+}
+
+#[test]
+fn tasks_skip_code_blocks() {
+	let tasks = extract_tasks("- [ ] one due:2025-02-01\n```\n- [ ] code\n```\n1. [x] two");
+	let found: Vec<_> = tasks.iter().map(|task| (task.line, task.text.as_str(), task.done)).collect();
+	assert_eq!(found, [(1, "one", false), (5, "two", true)]);
+	assert!(tasks[0].due.is_some());
+}
+
+#[test]
+fn wikilinks_skip_code() {
+	let content = "See [[Plan]] and [[plan|the plan]].\n\n`[[Inline]]`\n\n```\n[[Fenced]]\n```\n\n- [[Release notes]]";
+	assert_eq!(extract_wikilinks(content), ["Plan", "Release notes"]);
+}
+
+#[test]
+fn frontmatter_tags_may_be_a_list() {
+	let parsed =
+		parse_markdown_file("---\nstatus: open\ntags:\n  - work\n  - \"@daily\"\n---\nStandup\n@work @team\n\nBody")
+			.expect("not empty");
+	assert_eq!(parsed.tags, ["work", "daily"]);
+	assert_eq!(parsed.properties.get("status"), Some(&PropertyValue::Text("open".to_string())));
+	assert_eq!(parsed.content, "@work @team\n\nBody");
+
+	let inline = parse_markdown_file("---\ntags: [work, daily]\n---\nStandup").expect("not empty");
+	assert_eq!(inline.tags, ["work", "daily"]);
+}
+
+#[test]
+fn inline_tags_skip_email_addresses_and_read_old_exports() {
+	let plain = parse_markdown_file("Ping\nMail bob@example.com @ops").expect("not empty");
+	assert_eq!(plain.tags, ["ops"]);
+	assert_eq!(plain.content, "Mail bob@example.com");
+
+	let old = "---\nuuid: 67e55044-10b1-426f-9247-bb680e5fe0c8\n---\nStandup\n@work @daily\n\nAsk @team";
+	let parsed = parse_markdown_file(old).expect("not empty");
+	assert_eq!(parsed.tags, ["work", "daily"]);
+	assert_eq!(parsed.content, "Ask @team");
+}
+
+#[test]
+fn other_dash_blocks_stay_in_the_note() {
+	let prose = "---\nA paragraph set off by rules\n---\nTitle";
+	let parsed = parse_markdown_file(prose).expect("not empty");
+	assert!(parsed.content.contains("A paragraph set off by rules"));
+
+	let nested = parse_markdown_file("---\nauthors:\n  - Ann\n---\nTitle").expect("not empty");
+	assert!(nested.properties.is_empty());
+	assert!(nested.content.contains("- Ann"));
+}